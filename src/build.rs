@@ -1,35 +1,162 @@
 use slog::Logger;
+use std::fmt;
 
 use file::FileLoggerBuilder;
+use multi::MultiLoggerBuilder;
 use null::NullLoggerBuilder;
+use syslog::SyslogLoggerBuilder;
 use terminal::TerminalLoggerBuilder;
+use types::{LevelHandle, Severity};
 use Result;
 
 /// This trait allows to build a logger instance.
 pub trait Build {
     /// Builds a logger.
     fn build(&self) -> Result<Logger>;
+
+    /// Builds a logger and a guard which finalizes the logger when dropped.
+    ///
+    /// When the guard is dropped, the log records in the asynchronous channel are flushed,
+    /// the worker thread of the logger is joined, and in-flight compressions of rotated files
+    /// are waited for.
+    /// Log records emitted after that may be discarded.
+    ///
+    /// The default implementation returns a guard which does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use]
+    /// extern crate slog;
+    /// extern crate sloggers;
+    ///
+    /// use sloggers::Build;
+    /// use sloggers::terminal::TerminalLoggerBuilder;
+    ///
+    /// # fn main() {
+    /// let (logger, _guard) = TerminalLoggerBuilder::new().build_with_guard().unwrap();
+    /// info!(logger, "This record is outputted before `main` returns");
+    /// # }
+    /// ```
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let logger = track!(self.build())?;
+        Ok((logger, LoggerGuard::new()))
+    }
+}
+
+/// A guard which finalizes a logger when dropped.
+///
+/// See the documentation of [`Build::build_with_guard`] for more details.
+///
+/// [`Build::build_with_guard`]: ./trait.Build.html#method.build_with_guard
+#[must_use]
+#[derive(Default)]
+pub struct LoggerGuard {
+    guards: Vec<Box<dyn Send>>,
+}
+impl LoggerGuard {
+    /// Makes a new `LoggerGuard` instance which does nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges the guard of another logger into this guard.
+    pub fn merge(&mut self, other: LoggerGuard) {
+        self.guards.extend(other.guards);
+    }
+
+    // Guards are dropped in the order they were added
+    pub(crate) fn add<T: Send + 'static>(&mut self, guard: T) {
+        self.guards.push(Box::new(guard));
+    }
+}
+impl fmt::Debug for LoggerGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LoggerGuard {{ guards: {} }}", self.guards.len())
+    }
 }
 
 /// Logger builder.
 #[derive(Debug)]
-#[cfg_attr(feature = "cargo-clippy", allow(large_enum_variant))]
+#[allow(clippy::large_enum_variant)]
 pub enum LoggerBuilder {
     /// File logger.
     File(FileLoggerBuilder),
 
+    /// Multi-destination logger.
+    Multi(MultiLoggerBuilder),
+
     /// Null logger.
     Null(NullLoggerBuilder),
 
+    /// Syslog logger.
+    Syslog(SyslogLoggerBuilder),
+
     /// Terminal logger.
     Terminal(TerminalLoggerBuilder),
 }
+impl LoggerBuilder {
+    /// Builds a logger and a handle for changing the log level of the logger at runtime.
+    ///
+    /// The handle of a null logger has no effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use]
+    /// extern crate slog;
+    /// extern crate sloggers;
+    ///
+    /// use sloggers::LoggerBuilder;
+    /// use sloggers::terminal::TerminalLoggerBuilder;
+    /// use sloggers::types::Severity;
+    ///
+    /// # fn main() {
+    /// let builder = LoggerBuilder::Terminal(TerminalLoggerBuilder::new());
+    /// let (logger, handle) = builder.build_with_level_handle().unwrap();
+    ///
+    /// handle.set(Severity::Warning);
+    /// info!(logger, "This record is discarded");
+    /// # }
+    /// ```
+    pub fn build_with_level_handle(&self) -> Result<(Logger, LevelHandle)> {
+        track!(self.build_logger(None))
+    }
+
+    pub(crate) fn build_logger(
+        &self,
+        guard: Option<&mut LoggerGuard>,
+    ) -> Result<(Logger, LevelHandle)> {
+        match *self {
+            LoggerBuilder::File(ref b) => track!(b.build_logger(guard)),
+            LoggerBuilder::Multi(ref b) => track!(b.build_logger(guard)),
+            LoggerBuilder::Null(ref b) => {
+                let logger = track!(b.build())?;
+                Ok((logger, LevelHandle::new(Severity::default())))
+            }
+            LoggerBuilder::Syslog(ref b) => track!(b.build_logger(guard)),
+            LoggerBuilder::Terminal(ref b) => track!(b.build_logger(guard)),
+        }
+    }
+}
 impl Build for LoggerBuilder {
     fn build(&self) -> Result<Logger> {
         match *self {
             LoggerBuilder::File(ref b) => track!(b.build()),
+            LoggerBuilder::Multi(ref b) => track!(b.build()),
             LoggerBuilder::Null(ref b) => track!(b.build()),
+            LoggerBuilder::Syslog(ref b) => track!(b.build()),
             LoggerBuilder::Terminal(ref b) => track!(b.build()),
         }
     }
+
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        match *self {
+            LoggerBuilder::File(ref b) => track!(b.build_with_guard()),
+            LoggerBuilder::Multi(ref b) => track!(b.build_with_guard()),
+            LoggerBuilder::Null(ref b) => track!(b.build_with_guard()),
+            LoggerBuilder::Syslog(ref b) => track!(b.build_with_guard()),
+            LoggerBuilder::Terminal(ref b) => track!(b.build_with_guard()),
+        }
+    }
 }
@@ -1,10 +1,15 @@
 use slog::Logger;
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
 
 use file::FileLoggerConfig;
+use multi::MultiLoggerConfig;
 use null::NullLoggerConfig;
+use syslog::SyslogLoggerConfig;
 use terminal::TerminalLoggerConfig;
 use types::Severity;
-use {Build, LoggerBuilder, Result};
+use {Build, Error, ErrorKind, LoggerBuilder, Result};
 
 /// Configuration of a logger builder.
 pub trait Config {
@@ -76,13 +81,59 @@ pub trait Config {
 /// let _config: LoggerConfig = serdeconv::from_toml_str(toml).unwrap();
 /// # }
 /// ```
+///
+/// Multi-destination logger.
+///
+/// ```
+/// extern crate sloggers;
+/// extern crate serdeconv;
+///
+/// use sloggers::LoggerConfig;
+///
+/// # fn main() {
+/// let toml = r#"
+/// type = "multi"
+///
+/// [[loggers]]
+/// type = "terminal"
+/// level = "info"
+///
+/// [[loggers]]
+/// type = "file"
+/// level = "debug"
+/// path = "/path/to/file.log"
+/// "#;
+/// let _config: LoggerConfig = serdeconv::from_toml_str(toml).unwrap();
+/// # }
+/// ```
+///
+/// Syslog logger.
+///
+/// ```
+/// extern crate sloggers;
+/// extern crate serdeconv;
+///
+/// use sloggers::LoggerConfig;
+///
+/// # fn main() {
+/// let toml = r#"
+/// type = "syslog"
+/// format = "rfc5424"
+/// facility = "local0"
+/// destination = { udp = "127.0.0.1:514" }
+/// "#;
+/// let _config: LoggerConfig = serdeconv::from_toml_str(toml).unwrap();
+/// # }
+/// ```
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "lowercase")]
 pub enum LoggerConfig {
     File(FileLoggerConfig),
+    Multi(MultiLoggerConfig),
     Null(NullLoggerConfig),
+    Syslog(SyslogLoggerConfig),
     Terminal(TerminalLoggerConfig),
 }
 impl LoggerConfig {
@@ -90,17 +141,170 @@ impl LoggerConfig {
     pub fn set_loglevel(&mut self, level: Severity) {
         match *self {
             LoggerConfig::File(ref mut c) => c.level = level,
+            LoggerConfig::Multi(ref mut c) => {
+                for child in &mut c.loggers {
+                    child.set_loglevel(level);
+                }
+            }
             LoggerConfig::Null(_) => {}
+            LoggerConfig::Syslog(ref mut c) => c.level = level,
             LoggerConfig::Terminal(ref mut c) => c.level = level,
         }
     }
+
+    /// Overrides the fields of this configuration with environment variables.
+    ///
+    /// The following variables are recognized (`${PREFIX}` is replaced with `prefix`):
+    ///
+    /// | Variable | Field | Loggers |
+    /// |----------|-------|---------|
+    /// | `${PREFIX}_LEVEL` | `level` | file, syslog, terminal |
+    /// | `${PREFIX}_LEVELS` | `levels` | file, terminal |
+    /// | `${PREFIX}_FORMAT` | `format` | file, terminal |
+    /// | `${PREFIX}_SYSLOG_FORMAT` | `format` | syslog |
+    /// | `${PREFIX}_SOURCE_LOCATION` | `source_location` | file, syslog, terminal |
+    /// | `${PREFIX}_TIMEZONE` | `timezone` | file, syslog, terminal |
+    /// | `${PREFIX}_TIMESTAMP_FORMAT` | `timestamp_format` | file, terminal |
+    /// | `${PREFIX}_DESTINATION` | `destination` | terminal |
+    /// | `${PREFIX}_PATH` | `path` | file |
+    /// | `${PREFIX}_FACILITY` | `facility` | syslog |
+    ///
+    /// The values are parsed in the same way as the configuration text
+    /// (e.g., `"debug"` for `Severity::Debug`).
+    /// Since the formats of syslog loggers (`rfc3164` and `rfc5424`) differ from the others,
+    /// they are specified by a separate variable.
+    /// For multi-destination loggers, only `${PREFIX}_LEVEL`, `${PREFIX}_SOURCE_LOCATION` and
+    /// `${PREFIX}_TIMEZONE` are applied to every child logger (except null loggers).
+    /// The other variables are ignored, because they are not applicable to every kind of
+    /// child logger, or would make the children share a single value (e.g., the same file path).
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ErrorKind::Invalid` error if a variable has a malformed value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate sloggers;
+    /// extern crate serdeconv;
+    ///
+    /// use sloggers::LoggerConfig;
+    /// use sloggers::types::Severity;
+    /// use std::env;
+    ///
+    /// # fn main() {
+    /// let mut config: LoggerConfig = serdeconv::from_toml_str(r#"
+    /// type = "terminal"
+    /// level = "info"
+    /// "#).unwrap();
+    ///
+    /// env::set_var("APP_LOG_LEVEL", "debug");
+    /// config.apply_env("APP_LOG").unwrap();
+    /// if let LoggerConfig::Terminal(ref c) = config {
+    ///     assert_eq!(c.level, Severity::Debug);
+    /// }
+    ///
+    /// env::set_var("APP_LOG_LEVEL", "verbose");
+    /// assert!(config.apply_env("APP_LOG").is_err());
+    /// # }
+    /// ```
+    pub fn apply_env(&mut self, prefix: &str) -> Result<()> {
+        match *self {
+            LoggerConfig::File(ref mut c) => {
+                track!(override_with_env(&mut c.level, prefix, "LEVEL"))?;
+                track!(override_option_with_env(&mut c.levels, prefix, "LEVELS"))?;
+                track!(override_with_env(&mut c.format, prefix, "FORMAT"))?;
+                track!(override_with_env(
+                    &mut c.source_location,
+                    prefix,
+                    "SOURCE_LOCATION"
+                ))?;
+                track!(override_with_env(&mut c.timezone, prefix, "TIMEZONE"))?;
+                track!(override_with_env(
+                    &mut c.timestamp_format,
+                    prefix,
+                    "TIMESTAMP_FORMAT"
+                ))?;
+                if let Some(path) = env::var_os(env_key(prefix, "PATH")) {
+                    c.path = PathBuf::from(path);
+                }
+            }
+            LoggerConfig::Multi(ref mut c) => {
+                for child in &mut c.loggers {
+                    track!(child.apply_common_env(prefix))?;
+                }
+            }
+            LoggerConfig::Null(_) => {}
+            LoggerConfig::Syslog(ref mut c) => {
+                track!(override_with_env(&mut c.level, prefix, "LEVEL"))?;
+                track!(override_with_env(&mut c.format, prefix, "SYSLOG_FORMAT"))?;
+                track!(override_with_env(
+                    &mut c.source_location,
+                    prefix,
+                    "SOURCE_LOCATION"
+                ))?;
+                track!(override_with_env(&mut c.timezone, prefix, "TIMEZONE"))?;
+                track!(override_with_env(&mut c.facility, prefix, "FACILITY"))?;
+            }
+            LoggerConfig::Terminal(ref mut c) => {
+                track!(override_with_env(&mut c.level, prefix, "LEVEL"))?;
+                track!(override_option_with_env(&mut c.levels, prefix, "LEVELS"))?;
+                track!(override_with_env(&mut c.format, prefix, "FORMAT"))?;
+                track!(override_with_env(
+                    &mut c.source_location,
+                    prefix,
+                    "SOURCE_LOCATION"
+                ))?;
+                track!(override_with_env(&mut c.timezone, prefix, "TIMEZONE"))?;
+                track!(override_with_env(
+                    &mut c.timestamp_format,
+                    prefix,
+                    "TIMESTAMP_FORMAT"
+                ))?;
+                track!(override_with_env(&mut c.destination, prefix, "DESTINATION"))?;
+            }
+        }
+        Ok(())
+    }
+
+    // Applies the variables which are applicable to every kind of logger
+    fn apply_common_env(&mut self, prefix: &str) -> Result<()> {
+        let (level, source_location, timezone) = match *self {
+            LoggerConfig::File(ref mut c) => {
+                (&mut c.level, &mut c.source_location, &mut c.timezone)
+            }
+            LoggerConfig::Multi(ref mut c) => {
+                for child in &mut c.loggers {
+                    track!(child.apply_common_env(prefix))?;
+                }
+                return Ok(());
+            }
+            LoggerConfig::Null(_) => return Ok(()),
+            LoggerConfig::Syslog(ref mut c) => {
+                (&mut c.level, &mut c.source_location, &mut c.timezone)
+            }
+            LoggerConfig::Terminal(ref mut c) => {
+                (&mut c.level, &mut c.source_location, &mut c.timezone)
+            }
+        };
+        track!(override_with_env(level, prefix, "LEVEL"))?;
+        track!(override_with_env(
+            source_location,
+            prefix,
+            "SOURCE_LOCATION"
+        ))?;
+        track!(override_with_env(timezone, prefix, "TIMEZONE"))?;
+        Ok(())
+    }
 }
 impl Config for LoggerConfig {
     type Builder = LoggerBuilder;
     fn try_to_builder(&self) -> Result<Self::Builder> {
         match *self {
             LoggerConfig::File(ref c) => track!(c.try_to_builder()).map(LoggerBuilder::File),
+            LoggerConfig::Multi(ref c) => track!(c.try_to_builder()).map(LoggerBuilder::Multi),
             LoggerConfig::Null(ref c) => track!(c.try_to_builder()).map(LoggerBuilder::Null),
+            LoggerConfig::Syslog(ref c) => track!(c.try_to_builder()).map(LoggerBuilder::Syslog),
             LoggerConfig::Terminal(ref c) => {
                 track!(c.try_to_builder()).map(LoggerBuilder::Terminal)
             }
@@ -112,3 +316,31 @@ impl Default for LoggerConfig {
         LoggerConfig::Terminal(TerminalLoggerConfig::default())
     }
 }
+
+fn env_key(prefix: &str, name: &str) -> String {
+    format!("{}_{}", prefix, name)
+}
+
+fn override_with_env<T>(field: &mut T, prefix: &str, name: &str) -> Result<()>
+where
+    T: FromStr<Err = Error>,
+{
+    let key = env_key(prefix, name);
+    if let Some(value) = env::var_os(&key) {
+        let value = track_assert_some!(value.to_str(), ErrorKind::Invalid, "key={:?}", key);
+        *field = track!(value.parse(), "key={:?}", key)?;
+    }
+    Ok(())
+}
+
+fn override_option_with_env<T>(field: &mut Option<T>, prefix: &str, name: &str) -> Result<()>
+where
+    T: FromStr<Err = Error>,
+{
+    let key = env_key(prefix, name);
+    if let Some(value) = env::var_os(&key) {
+        let value = track_assert_some!(value.to_str(), ErrorKind::Invalid, "key={:?}", key);
+        *field = Some(track!(value.parse(), "key={:?}", key)?);
+    }
+    Ok(())
+}
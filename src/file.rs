@@ -1,21 +1,39 @@
 //! File logger.
-use chrono::{DateTime, Local, TimeZone as ChronoTimeZone, Utc};
+use chrono::format::{self as chrono_format, Parsed, StrftimeItems};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone as ChronoTimeZone, Timelike, Utc,
+};
 use libflate::gzip::Encoder as GzipEncoder;
-use slog::{Drain, FnValue, Logger};
-use slog_async::Async;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use slog::{Drain, Level, Logger, OwnedKVList, Record};
 use slog_kvfilter::KVFilter;
 use slog_term::{CompactFormat, FullFormat, PlainDecorator};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Write};
+use std::mem;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc;
+use std::str::FromStr;
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use trackable::error::ErrorKindExt;
 
-use misc::{module_and_line, timezone_to_timestamp_fn};
-use types::KVFilterParameters;
-use types::{Format, Severity, SourceLocation, TimeZone};
-use {Build, Config, ErrorKind, Result};
+use misc::{
+    format_timestamp, json_drain, source_location_logger, with_fields, with_metadata, AsyncDrain,
+    AsyncOrSyncDrain, LogfmtDrain, Timestamper,
+};
+use rate_limit::RateLimitDrain;
+use redact::{RedactDrain, Redactor};
+use template::{Template, TemplateDrain};
+use types::{DroppedRecordCounter, OverflowStrategy};
+use types::{
+    FieldValue, Format, MetadataFields, Severity, SourceLocation, TimeZone, TimestampFormat,
+};
+use types::{KVFilterParameters, LevelDirectives, LevelHandle, RateLimit};
+use {Build, Config, Error, ErrorKind, LoggerGuard, Result};
 
 /// A logger builder which build loggers that write log records to the specified file.
 ///
@@ -24,10 +42,20 @@ use {Build, Config, ErrorKind, Result};
 pub struct FileLoggerBuilder {
     format: Format,
     source_location: SourceLocation,
+    source_location_key: String,
+    metadata: MetadataFields,
+    fields: Vec<(String, FieldValue)>,
+    redactor: Redactor,
+    rate_limit: Option<RateLimit>,
     timezone: TimeZone,
+    timestamp_format: TimestampFormat,
     level: Severity,
+    level_directives: LevelDirectives,
     appender: FileAppender,
     channel_size: usize,
+    sync: bool,
+    overflow_strategy: OverflowStrategy,
+    dropped_records: DroppedRecordCounter,
     kvfilterparameters: Option<KVFilterParameters>,
 }
 impl FileLoggerBuilder {
@@ -39,10 +67,20 @@ impl FileLoggerBuilder {
         FileLoggerBuilder {
             format: Format::default(),
             source_location: SourceLocation::default(),
+            source_location_key: default_source_location_key(),
+            metadata: MetadataFields::default(),
+            fields: Vec::new(),
+            redactor: Redactor::default(),
+            rate_limit: None,
             timezone: TimeZone::default(),
+            timestamp_format: TimestampFormat::default(),
             level: Severity::default(),
+            level_directives: LevelDirectives::default(),
             appender: FileAppender::new(path),
             channel_size: 1024,
+            sync: false,
+            overflow_strategy: OverflowStrategy::default(),
+            dropped_records: DroppedRecordCounter::new(),
             kvfilterparameters: None,
         }
     }
@@ -59,9 +97,70 @@ impl FileLoggerBuilder {
         self
     }
 
+    /// Sets the key under which the source code location is added to log records.
+    ///
+    /// The default value is `"module"`.
+    pub fn source_location_key(&mut self, key: &str) -> &mut Self {
+        self.source_location_key = key.to_owned();
+        self
+    }
+
+    /// Sets the metadata fields (e.g., process ID and thread name) added to every log record.
+    pub fn metadata(&mut self, fields: MetadataFields) -> &mut Self {
+        self.metadata = fields;
+        self
+    }
+
+    /// Adds a static key/value pair to every log record.
+    pub fn field<V: Into<FieldValue>>(&mut self, key: &str, value: V) -> &mut Self {
+        self.fields.push((key.to_owned(), value.into()));
+        self
+    }
+
+    /// Adds a key whose values are replaced with `"[REDACTED]"` in log records.
+    ///
+    /// Keys are compared case-insensitively.
+    pub fn redact_key(&mut self, key: &str) -> &mut Self {
+        self.redactor.add_key(key);
+        self
+    }
+
+    /// Adds a pattern whose matches in the messages and the string values of log records
+    /// are replaced with `"[REDACTED]"`.
+    pub fn redact_pattern(&mut self, pattern: Regex) -> &mut Self {
+        self.redactor.add_pattern(pattern);
+        self
+    }
+
+    /// Sets the rate limit of log records.
+    ///
+    /// See the documentation of [`RateLimit`] for more details.
+    ///
+    /// [`RateLimit`]: ../types/struct.RateLimit.html
+    pub fn rate_limit(&mut self, limit: RateLimit) -> &mut Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
     /// Sets the time zone which this logger will use.
+    ///
+    /// This is also used for determining the boundaries of [`rotate_interval`].
+    ///
+    /// [`rotate_interval`]: ./struct.FileLoggerBuilder.html#method.rotate_interval
     pub fn timezone(&mut self, timezone: TimeZone) -> &mut Self {
         self.timezone = timezone;
+        self.appender.timezone = timezone;
+        self
+    }
+
+    /// Sets the format of timestamps in log records.
+    ///
+    /// Note that this does not affect the timestamps in the names of rotated files
+    /// (see [`timestamp_template`]).
+    ///
+    /// [`timestamp_template`]: #method.timestamp_template
+    pub fn timestamp_format(&mut self, format: TimestampFormat) -> &mut Self {
+        self.timestamp_format = format;
         self
     }
 
@@ -71,12 +170,58 @@ impl FileLoggerBuilder {
         self
     }
 
+    /// Sets per-module log level directives.
+    ///
+    /// The directives take precedence over the log level set by [`level`]
+    /// for the matching modules.
+    ///
+    /// [`level`]: #method.level
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sloggers::file::FileLoggerBuilder;
+    ///
+    /// let mut builder = FileLoggerBuilder::new("/tmp/foo.log");
+    /// builder.level_directives("info,my_crate::db=debug,hyper=warn".parse().unwrap());
+    /// ```
+    pub fn level_directives(&mut self, directives: LevelDirectives) -> &mut Self {
+        self.level_directives = directives;
+        self
+    }
+
     /// Sets the size of the asynchronous channel of this logger.
     pub fn channel_size(&mut self, channel_size: usize) -> &mut Self {
         self.channel_size = channel_size;
         self
     }
 
+    /// Sets whether this logger processes log records synchronously or not.
+    ///
+    /// If `true` is specified, log records are outputted by the calling thread
+    /// (the formatted drain is protected by a mutex) instead of a background thread.
+    /// It is slower but no log records are lost when the process exits.
+    /// The settings of the asynchronous channel (e.g., `channel_size`) are ignored in that case.
+    ///
+    /// The default value is `false`.
+    pub fn sync(&mut self, sync: bool) -> &mut Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Sets the behavior of this logger when its asynchronous channel is full.
+    pub fn overflow_strategy(&mut self, overflow_strategy: OverflowStrategy) -> &mut Self {
+        self.overflow_strategy = overflow_strategy;
+        self
+    }
+
+    /// Returns the counter of the log records dropped due to overflows of the asynchronous channel.
+    ///
+    /// The counter is shared by all the loggers built by this builder.
+    pub fn dropped_record_counter(&self) -> DroppedRecordCounter {
+        self.dropped_records.clone()
+    }
+
     /// Sets [`KVFilter`].
     ///
     /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
@@ -109,6 +254,23 @@ impl FileLoggerBuilder {
         self
     }
 
+    /// Sets the interval at which the current log file is rotated.
+    ///
+    /// The boundaries of the intervals are determined in the time zone of this logger.
+    /// If [`rotate_size`] is also specified, the file will be rotated
+    /// when either of the conditions is satisfied.
+    ///
+    /// If a non-empty log file already exists when the logger is opened,
+    /// the modification time of the file is used as the start of the current interval.
+    ///
+    /// The default value is `RotateInterval::Never`.
+    ///
+    /// [`rotate_size`]: ./struct.FileLoggerBuilder.html#method.rotate_size
+    pub fn rotate_interval(&mut self, interval: RotateInterval) -> &mut Self {
+        self.appender.rotate_interval = interval;
+        self
+    }
+
     /// Sets the maximum number of rotated log files to keep.
     ///
     /// If the number of rotated log files exceed this value, the oldest log file will be deleted.
@@ -119,27 +281,164 @@ impl FileLoggerBuilder {
         self
     }
 
+    /// Sets the naming scheme of rotated log files.
+    ///
+    /// If `RotateNaming::Timestamp` is specified, the current log file will be renamed to
+    /// `"${ORIGINAL_FILE_NAME}.${TIMESTAMP}"` at rotation instead of shifting
+    /// the sequence numbers of all the previously rotated files.
+    /// `${TIMESTAMP}` is the start of the interval which the file covers (or the rotation time
+    /// if no [`rotate_interval`] is specified) formatted according to [`timestamp_template`] in
+    /// the time zone of this logger. If the name conflicts with an existing file,
+    /// a sequence number is appended (e.g., `"foo.log.20180918_1019.1"`).
+    ///
+    /// In this case, the number of rotated files specified by [`rotate_keep`] is enforced
+    /// by listing the files that match the naming scheme and deleting the oldest ones
+    /// (determined by the timestamps and the sequence numbers in their names).
+    ///
+    /// The default value is `RotateNaming::Sequential`.
+    ///
+    /// [`rotate_interval`]: ./struct.FileLoggerBuilder.html#method.rotate_interval
+    /// [`timestamp_template`]: ./struct.FileLoggerBuilder.html#method.timestamp_template
+    /// [`rotate_keep`]: ./struct.FileLoggerBuilder.html#method.rotate_keep
+    pub fn rotate_naming(&mut self, naming: RotateNaming) -> &mut Self {
+        self.appender.rotate_naming = naming;
+        self
+    }
+
+    /// Sets the format string of the timestamps used in the names of rotated files.
+    ///
+    /// The string is formatted using [strftime](https://docs.rs/chrono/0.4.6/chrono/format/strftime/index.html#specifiers).
+    ///
+    /// The default value is `"%Y%m%d_%H%M"`.
+    pub fn timestamp_template(&mut self, template: &str) -> &mut Self {
+        self.appender.timestamp_template = template.to_owned();
+        self
+    }
+
     /// Sets whether to compress or not compress rotated files.
     ///
     /// If `true` is specified, rotated files will be compressed by GZIP algorithm and
     /// the suffix ".gz" will be appended to those file names.
     ///
+    /// Compressions are done in background threads.
+    /// While a compression is in progress, the next rotation is deferred until it finishes,
+    /// and the errors of compressions are reported as log records.
+    ///
     /// The default value is `false`.
     pub fn rotate_compress(&mut self, compress: bool) -> &mut Self {
         self.appender.rotate_compress = compress;
         self
     }
 
-    fn build_with_drain<D>(&self, drain: D) -> Logger
+    /// Builds a logger and a handle for changing the log level of the logger at runtime.
+    pub fn build_with_level_handle(&self) -> Result<(Logger, LevelHandle)> {
+        track!(self.build_logger(None))
+    }
+
+    pub(crate) fn build_logger(
+        &self,
+        mut guard: Option<&mut LoggerGuard>,
+    ) -> Result<(Logger, LevelHandle)> {
+        if let Some(limit) = self.rate_limit {
+            track!(limit.validate())?;
+        }
+        let severity = self.level_directives.default_severity();
+        let level = LevelHandle::new(severity.unwrap_or(self.level))
+            .with_directives(&self.level_directives);
+        let timestamper = track!(Timestamper::new(
+            self.timestamp_format.clone(),
+            self.timezone
+        ))?;
+        let appender = self.appender.clone();
+        let compression_guard = CompressionGuard(appender.wait_compression.clone());
+        let compression_errors = appender.compression_errors.clone();
+        let async_guard = guard.as_deref_mut();
+        let mut metadata = self.metadata.clone();
+        let logger = match self.format {
+            Format::Full => {
+                let decorator = PlainDecorator::new(appender);
+                let format = FullFormat::new(decorator)
+                    .use_custom_timestamp(move |w: &mut dyn io::Write| timestamper.write(w));
+                self.build_with_drain(format.build(), &level, async_guard, compression_errors)
+            }
+            Format::Compact => {
+                let decorator = PlainDecorator::new(appender);
+                let format = CompactFormat::new(decorator)
+                    .use_custom_timestamp(move |w: &mut dyn io::Write| timestamper.write(w));
+                self.build_with_drain(format.build(), &level, async_guard, compression_errors)
+            }
+            Format::Json => {
+                let drain = json_drain(appender, timestamper);
+                self.build_with_drain(drain, &level, async_guard, compression_errors)
+            }
+            Format::Logfmt => {
+                let drain = LogfmtDrain::new(appender, timestamper);
+                self.build_with_drain(drain, &level, async_guard, compression_errors)
+            }
+            Format::Template(ref template) => {
+                let template = track!(template.parse::<Template>())?;
+                metadata.thread_name |= template.uses_thread();
+                let drain = TemplateDrain::new(appender, template, timestamper);
+                self.build_with_drain(drain, &level, async_guard, compression_errors)
+            }
+        };
+        let logger = with_fields(logger, &self.fields);
+        let logger = with_metadata(logger, &metadata);
+
+        // This must be dropped after the worker thread, which may start a compression, is joined
+        if let Some(guard) = guard {
+            guard.add(compression_guard);
+        }
+        Ok((logger, level))
+    }
+
+    fn build_with_drain<D>(
+        &self,
+        drain: D,
+        level: &LevelHandle,
+        mut guard: Option<&mut LoggerGuard>,
+        compression_errors: CompressionErrors,
+    ) -> Logger
     where
         D: Drain + Send + 'static,
         D::Err: Debug,
     {
-        // async inside, level and key value filters outside for speed
-        let drain = Async::new(drain.fuse())
-            .chan_size(self.channel_size)
-            .build()
-            .fuse();
+        let drain = CompressionErrorDrain {
+            drain,
+            errors: compression_errors,
+        };
+        let drain = RedactDrain::new(drain, self.redactor.clone());
+
+        // The source code location is controlled by the template
+        let source_location = match self.format {
+            Format::Template(_) => SourceLocation::None,
+            _ => self.source_location,
+        };
+
+        // The worker thread must be joined after the pending summaries of the rate limit are outputted
+        let mut async_guard = guard.as_ref().map(|_| LoggerGuard::new());
+
+        // async (or mutex) inside, level and key value filters outside for speed
+        let drain = if self.sync {
+            AsyncOrSyncDrain::Sync(Mutex::new(drain.fuse()).fuse())
+        } else {
+            AsyncOrSyncDrain::Async(
+                AsyncDrain::new(
+                    drain.fuse(),
+                    self.channel_size,
+                    self.overflow_strategy,
+                    self.dropped_records.clone(),
+                    async_guard.as_mut(),
+                )
+                .fuse(),
+            )
+        };
+
+        // The rate limit is applied after the filters, so that filtered out records do not use it up
+        let drain = RateLimitDrain::new(drain, self.rate_limit, guard.as_deref_mut());
+        if let (Some(guard), Some(async_guard)) = (guard, async_guard) {
+            guard.merge(async_guard);
+        }
 
         if let Some(ref p) = self.kvfilterparameters {
             let kvdrain = KVFilter::new(drain, p.severity.as_level())
@@ -148,42 +447,31 @@ impl FileLoggerBuilder {
                 .always_suppress_on_regex(p.always_suppress_on_regex.clone())
                 .only_pass_on_regex(p.only_pass_on_regex.clone());
 
-            let drain = self.level.set_level_filter(kvdrain.fuse());
+            let drain = level
+                .set_level_filter(kvdrain.fuse())
+                .directives(self.level_directives.clone());
 
-            match self.source_location {
-                SourceLocation::None => Logger::root(drain.fuse(), o!()),
-                SourceLocation::ModuleAndLine => {
-                    Logger::root(drain.fuse(), o!("module" => FnValue(module_and_line)))
-                }
-            }
+            source_location_logger(drain.fuse(), source_location, &self.source_location_key)
         } else {
-            let drain = self.level.set_level_filter(drain.fuse());
+            let drain = level
+                .set_level_filter(drain.fuse())
+                .directives(self.level_directives.clone());
 
-            match self.source_location {
-                SourceLocation::None => Logger::root(drain.fuse(), o!()),
-                SourceLocation::ModuleAndLine => {
-                    Logger::root(drain.fuse(), o!("module" => FnValue(module_and_line)))
-                }
-            }
+            source_location_logger(drain.fuse(), source_location, &self.source_location_key)
         }
     }
 }
 impl Build for FileLoggerBuilder {
     fn build(&self) -> Result<Logger> {
-        let decorator = PlainDecorator::new(self.appender.clone());
-        let timestamp = timezone_to_timestamp_fn(self.timezone);
-        let logger = match self.format {
-            Format::Full => {
-                let format = FullFormat::new(decorator).use_custom_timestamp(timestamp);
-                self.build_with_drain(format.build())
-            }
-            Format::Compact => {
-                let format = CompactFormat::new(decorator).use_custom_timestamp(timestamp);
-                self.build_with_drain(format.build())
-            }
-        };
+        let (logger, _) = track!(self.build_with_level_handle())?;
         Ok(logger)
     }
+
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let mut guard = LoggerGuard::new();
+        let (logger, _) = track!(self.build_logger(Some(&mut guard)))?;
+        Ok((logger, guard))
+    }
 }
 
 #[derive(Debug)]
@@ -195,7 +483,15 @@ struct FileAppender {
     rotate_size: u64,
     rotate_keep: usize,
     rotate_compress: bool,
-    wait_compression: Option<mpsc::Receiver<io::Result<()>>>,
+    rotate_interval: RotateInterval,
+    rotate_naming: RotateNaming,
+    timestamp_template: String,
+    timezone: TimeZone,
+    interval_start: Option<DateTime<Utc>>,
+    next_rotation: Option<DateTime<Utc>>,
+    in_record: bool,
+    wait_compression: Arc<Mutex<Option<mpsc::Receiver<io::Result<()>>>>>,
+    compression_errors: CompressionErrors,
 }
 impl Clone for FileAppender {
     fn clone(&self) -> Self {
@@ -207,7 +503,15 @@ impl Clone for FileAppender {
             rotate_size: self.rotate_size,
             rotate_keep: self.rotate_keep,
             rotate_compress: self.rotate_compress,
-            wait_compression: None,
+            rotate_interval: self.rotate_interval.clone(),
+            rotate_naming: self.rotate_naming,
+            timestamp_template: self.timestamp_template.clone(),
+            timezone: self.timezone,
+            interval_start: None,
+            next_rotation: None,
+            in_record: false,
+            wait_compression: Arc::new(Mutex::new(None)),
+            compression_errors: Arc::new(Mutex::new(Vec::new())),
         }
     }
 }
@@ -221,7 +525,15 @@ impl FileAppender {
             rotate_size: default_rotate_size(),
             rotate_keep: default_rotate_keep(),
             rotate_compress: false,
-            wait_compression: None,
+            rotate_interval: RotateInterval::default(),
+            rotate_naming: RotateNaming::default(),
+            timestamp_template: default_timestamp_template(),
+            timezone: TimeZone::default(),
+            interval_start: None,
+            next_rotation: None,
+            in_record: false,
+            wait_compression: Arc::new(Mutex::new(None)),
+            compression_errors: Arc::new(Mutex::new(Vec::new())),
         }
     }
     fn reopen_if_needed(&mut self) -> io::Result<()> {
@@ -235,33 +547,46 @@ impl FileAppender {
                 .append(!self.truncate)
                 .write(true)
                 .open(&self.path)?;
-            self.written_size = file.metadata()?.len();
+            let metadata = file.metadata()?;
+            self.written_size = metadata.len();
+            let last_modified = if self.written_size > 0 {
+                metadata
+                    .modified()
+                    .map(DateTime::from)
+                    .unwrap_or_else(|_| Utc::now())
+            } else {
+                Utc::now()
+            };
+            self.interval_start = self
+                .rotate_interval
+                .interval_start(last_modified, self.timezone);
+            self.next_rotation = self
+                .rotate_interval
+                .next_rotation(last_modified, self.timezone);
             self.file = Some(file);
         }
         Ok(())
     }
     fn rotate(&mut self) -> io::Result<()> {
-        if let Some(ref mut rx) = self.wait_compression {
-            use std::sync::mpsc::TryRecvError;
-            match rx.try_recv() {
-                Err(TryRecvError::Empty) => {
-                    // The previous compression is in progress
-                    return Ok(());
-                }
-                Err(TryRecvError::Disconnected) => {
-                    let e =
-                        io::Error::new(io::ErrorKind::Other, "Log file compression thread aborted");
-                    return Err(e);
-                }
-                Ok(result) => {
-                    result?;
-                }
-            }
+        if self.is_compressing() {
+            // Defers this rotation until the previous compression finishes,
+            // since the file being compressed would be renamed otherwise
+            return Ok(());
         }
-        self.wait_compression = None;
 
         let _ = self.file.take();
 
+        match self.rotate_naming {
+            RotateNaming::Sequential => self.rotate_sequentially()?,
+            RotateNaming::Timestamp => self.rotate_with_timestamp()?,
+        }
+
+        self.written_size = 0;
+        self.reopen_if_needed()?;
+
+        Ok(())
+    }
+    fn rotate_sequentially(&mut self) -> io::Result<()> {
         for i in (1..self.rotate_keep + 1).rev() {
             let from = self.rotated_path(i)?;
             let to = self.rotated_path(i + 1)?;
@@ -273,15 +598,8 @@ impl FileAppender {
             let rotated_path = self.rotated_path(1)?;
             if self.rotate_compress {
                 let (plain_path, temp_gz_path) = self.rotated_paths_for_compression()?;
-                let (tx, rx) = mpsc::channel();
-
                 fs::rename(&self.path, &plain_path)?;
-                thread::spawn(move || {
-                    let result = Self::compress(plain_path, temp_gz_path, rotated_path);
-                    let _ = tx.send(result);
-                });
-
-                self.wait_compression = Some(rx);
+                self.start_compression(plain_path, temp_gz_path, rotated_path);
             } else {
                 fs::rename(&self.path, rotated_path)?;
             }
@@ -291,19 +609,133 @@ impl FileAppender {
         if delete_path.exists() {
             fs::remove_file(delete_path)?;
         }
+        Ok(())
+    }
+    fn rotate_with_timestamp(&mut self) -> io::Result<()> {
+        if !self.path.exists() {
+            return Ok(());
+        }
 
-        self.written_size = 0;
-        self.reopen_if_needed()?;
+        let plain_path = self.timestamped_path()?;
+        fs::rename(&self.path, &plain_path)?;
+        self.remove_old_timestamped_files()?;
 
+        if self.rotate_compress && plain_path.exists() {
+            let gz_path = PathBuf::from(format!("{}.gz", plain_path.display()));
+            let temp_gz_path = PathBuf::from(format!("{}.gz.temp", plain_path.display()));
+            self.start_compression(plain_path, temp_gz_path, gz_path);
+        }
         Ok(())
     }
-    fn rotated_path(&self, i: usize) -> io::Result<PathBuf> {
-        let path = self.path.to_str().ok_or_else(|| {
+    fn timestamped_path(&self) -> io::Result<PathBuf> {
+        let path = self.path_str()?;
+        // Rotated files are named after the start of the interval they cover
+        let date_time = self.interval_start.unwrap_or_else(Utc::now);
+        let timestamp = format_timestamp(&self.timestamp_template, self.timezone, date_time);
+        let base = format!("{}.{}", path, timestamp);
+
+        let mut candidate = base.clone();
+        let mut i = 0;
+        while Path::new(&candidate).exists() || Path::new(&format!("{}.gz", candidate)).exists() {
+            i += 1;
+            candidate = format!("{}.{}", base, i);
+        }
+        Ok(PathBuf::from(candidate))
+    }
+    fn remove_old_timestamped_files(&self) -> io::Result<()> {
+        let dir = match self.path.parent() {
+            Some(dir) if dir != Path::new("") => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let prefix = match self.path.file_name().and_then(|n| n.to_str()) {
+            Some(name) => format!("{}.", name),
+            None => return Ok(()),
+        };
+
+        let mut rotated_files = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let timestamp = entry.file_name().to_str().and_then(|name| {
+                name.strip_prefix(prefix.as_str())
+                    .and_then(|suffix| self.parse_timestamp_suffix(suffix))
+            });
+            if let Some(timestamp) = timestamp {
+                rotated_files.push((timestamp, entry.path()));
+            }
+        }
+        rotated_files.sort();
+
+        let excess = rotated_files.len().saturating_sub(self.rotate_keep);
+        for (_, path) in rotated_files.into_iter().take(excess) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+    // Returns the timestamp and the sequence number of a rotated file,
+    // or `None` if `suffix` does not match the timestamp template
+    fn parse_timestamp_suffix(&self, suffix: &str) -> Option<RotatedFileTimestamp> {
+        let suffix = suffix.strip_suffix(".gz").unwrap_or(suffix);
+        if let Some(timestamp) = parse_timestamp(suffix, &self.timestamp_template) {
+            return Some((timestamp.0, timestamp.1, 0));
+        }
+
+        // The suffix may have a sequence number for avoiding name conflicts
+        let i = suffix.rfind('.')?;
+        let seqno = suffix[i + 1..].parse::<u64>().ok()?;
+        let timestamp = parse_timestamp(&suffix[..i], &self.timestamp_template)?;
+        Some((timestamp.0, timestamp.1, seqno))
+    }
+    fn start_compression(&mut self, plain_path: PathBuf, temp_gz_path: PathBuf, gz_path: PathBuf) {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = Self::compress(plain_path, temp_gz_path, gz_path);
+            let _ = tx.send(result);
+        });
+        *self
+            .wait_compression
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(rx);
+    }
+    // Returns `true` if the previous compression is still in progress
+    fn is_compressing(&self) -> bool {
+        let mut wait_compression = self
+            .wait_compression
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let result = match *wait_compression {
+            None => return false,
+            Some(ref rx) => rx.try_recv(),
+        };
+        let error = match result {
+            Err(mpsc::TryRecvError::Empty) => return true,
+            Err(mpsc::TryRecvError::Disconnected) => Some(io::Error::new(
+                io::ErrorKind::Other,
+                "Log file compression thread aborted",
+            )),
+            Ok(result) => result.err(),
+        };
+        if let Some(e) = error {
+            self.compression_errors
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(e);
+        }
+        *wait_compression = None;
+        false
+    }
+    fn is_rotation_time(&self) -> bool {
+        self.next_rotation.map_or(false, |t| t <= Utc::now())
+    }
+    fn path_str(&self) -> io::Result<&str> {
+        self.path.to_str().ok_or_else(|| {
             io::Error::new(
                 io::ErrorKind::InvalidInput,
                 format!("Non UTF-8 log file path: {:?}", self.path),
             )
-        })?;
+        })
+    }
+    fn rotated_path(&self, i: usize) -> io::Result<PathBuf> {
+        let path = self.path_str()?;
         if self.rotate_compress {
             Ok(PathBuf::from(format!("{}.{}.gz", path, i)))
         } else {
@@ -311,12 +743,7 @@ impl FileAppender {
         }
     }
     fn rotated_paths_for_compression(&self) -> io::Result<(PathBuf, PathBuf)> {
-        let path = self.path.to_str().ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("Non UTF-8 log file path: {:?}", self.path),
-            )
-        })?;
+        let path = self.path_str()?;
         Ok((
             PathBuf::from(format!("{}.1", path)),
             PathBuf::from(format!("{}.1.gz.temp", path)),
@@ -336,6 +763,14 @@ impl FileAppender {
 impl Write for FileAppender {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.reopen_if_needed()?;
+        if !self.in_record {
+            // Checks the time boundary before the first byte of a record is written,
+            // so that the record goes to the file of the interval it belongs to
+            if self.is_rotation_time() {
+                self.rotate()?;
+            }
+            self.in_record = true;
+        }
         let size = if let Some(ref mut f) = self.file {
             f.write(buf)?
         } else {
@@ -352,6 +787,7 @@ impl Write for FileAppender {
         if let Some(ref mut f) = self.file {
             f.flush()?;
         }
+        self.in_record = false;
         if self.written_size >= self.rotate_size {
             self.rotate()?;
         }
@@ -359,75 +795,549 @@ impl Write for FileAppender {
     }
 }
 
-/// The configuration of `FileLoggerBuilder`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileLoggerConfig {
-    /// Log level.
-    #[serde(default)]
-    pub level: Severity,
+type CompressionErrors = Arc<Mutex<Vec<io::Error>>>;
 
-    /// Log record format.
-    #[serde(default)]
-    pub format: Format,
+// Reports the errors of the compressions of rotated files before the next record
+struct CompressionErrorDrain<D> {
+    drain: D,
+    errors: CompressionErrors,
+}
+impl<D: Drain> Drain for CompressionErrorDrain<D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+    fn log(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> ::std::result::Result<Self::Ok, Self::Err> {
+        let errors = mem::take(&mut *self.errors.lock().unwrap_or_else(|e| e.into_inner()));
+        for e in errors {
+            self.drain.log(
+                &record!(
+                    Level::Error,
+                    "",
+                    &format_args!("Cannot compress rotated log file: {}", e),
+                    b!("error" => e.to_string())
+                ),
+                logger_values,
+            )?;
+        }
+        self.drain.log(record, logger_values)
+    }
+    fn is_enabled(&self, level: Level) -> bool {
+        self.drain.is_enabled(level)
+    }
+}
 
-    /// Source code location
-    #[serde(default)]
-    pub source_location: SourceLocation,
+// Waits for the in-flight compression of a rotated file when dropped
+struct CompressionGuard(Arc<Mutex<Option<mpsc::Receiver<io::Result<()>>>>>);
+impl Drop for CompressionGuard {
+    fn drop(&mut self) {
+        let rx = self.0.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(rx) = rx {
+            let _ = rx.recv();
+        }
+    }
+}
 
-    /// Time Zone.
-    #[serde(default)]
-    pub timezone: TimeZone,
+/// The interval at which log files are rotated.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::file::RotateInterval;
+///
+/// assert_eq!(RotateInterval::default(), RotateInterval::Never);
+/// ```
+///
+/// Parses from a string:
+///
+/// ```
+/// use sloggers::file::RotateInterval;
+///
+/// assert_eq!("daily".parse::<RotateInterval>().ok(), Some(RotateInterval::Daily));
+///
+/// // Other strings are interpreted as cron expressions
+/// let cron = "30 4 * * 1-5".parse::<RotateInterval>().unwrap();
+/// assert_eq!(cron, RotateInterval::Cron("30 4 * * 1-5".parse().unwrap()));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RotateInterval {
+    /// Log files are never rotated by time.
+    #[default]
+    Never,
 
-    /// Format string for the timestamp in the path.
-    /// The string is formatted using [strftime](https://docs.rs/chrono/0.4.6/chrono/format/strftime/index.html#specifiers)
-    ///
-    /// Default: "%Y%m%d_%H%M", example: "20180918_1127"
-    #[serde(default = "default_timestamp_template")]
-    pub timestamp_template: String,
+    /// Rotates at the beginning of every hour.
+    Hourly,
 
-    /// Log file path template.
-    ///
-    /// It will be used as-is, with the following transformation:
-    ///
-    /// All occurrences of the substring "{timestamp}" will be replaced with the current timestamp
-    /// formatted according to `timestamp_template`. The timestamp will respect the `timezone` setting.
-    pub path: PathBuf,
+    /// Rotates at midnight.
+    Daily,
 
-    /// Asynchronous channel size
-    #[serde(default = "default_channel_size")]
-    pub channel_size: usize,
+    /// Rotates at midnight between Sunday and Monday.
+    Weekly,
 
-    /// Truncate the file or not
-    #[serde(default)]
-    pub truncate: bool,
+    /// Rotates at every time matching the cron expression.
+    Cron(CronSchedule),
+}
+impl RotateInterval {
+    fn next_rotation(&self, after: DateTime<Utc>, timezone: TimeZone) -> Option<DateTime<Utc>> {
+        match timezone {
+            TimeZone::Utc => self.next_rotation_in(&Utc, after),
+            TimeZone::Local => self.next_rotation_in(&Local, after),
+            TimeZone::Offset(offset) => self.next_rotation_in(&offset, after),
+            TimeZone::Named(tz) => self.next_rotation_in(&tz, after),
+        }
+    }
 
-    /// Log file rotation size.
-    ///
-    /// For details, see the documentation of [`rotate_size`].
-    ///
-    /// [`rotate_size`]: ./struct.FileLoggerBuilder.html#method.rotate_size
-    #[serde(default = "default_rotate_size")]
-    pub rotate_size: u64,
+    fn interval_start(&self, at: DateTime<Utc>, timezone: TimeZone) -> Option<DateTime<Utc>> {
+        match timezone {
+            TimeZone::Utc => self.interval_start_in(&Utc, at),
+            TimeZone::Local => self.interval_start_in(&Local, at),
+            TimeZone::Offset(offset) => self.interval_start_in(&offset, at),
+            TimeZone::Named(tz) => self.interval_start_in(&tz, at),
+        }
+    }
 
-    /// Maximum number of rotated log files to keep.
-    ///
-    /// For details, see the documentation of [`rotate_keep`].
-    ///
-    /// [`rotate_keep`]: ./struct.FileLoggerBuilder.html#method.rotate_keep
-    #[serde(default = "default_rotate_keep")]
-    pub rotate_keep: usize,
+    fn interval_start_in<Tz: ChronoTimeZone>(
+        &self,
+        timezone: &Tz,
+        at: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let local = at.with_timezone(timezone).naive_local();
+        let start = self.last_local_boundary(local)?;
 
-    /// Whether to compress or not compress rotated files.
-    ///
-    /// For details, see the documentation of [`rotate_keep`].
-    ///
-    /// [`rotate_compress`]: ./struct.FileLoggerBuilder.html#method.rotate_compress
-    ///
-    /// The default value is `false`.
-    #[serde(default)]
-    pub rotate_compress: bool,
+        // A boundary skipped by a DST transition is replaced with `at`
+        let start = timezone
+            .from_local_datetime(&start)
+            .earliest()
+            .map_or(at, |t| t.with_timezone(&Utc));
+        Some(start)
+    }
+
+    fn next_rotation_in<Tz: ChronoTimeZone>(
+        &self,
+        timezone: &Tz,
+        after: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let mut local = after.with_timezone(timezone).naive_local();
+        loop {
+            // Local times skipped by DST transitions are passed over
+            local = self.next_local_boundary(local)?;
+            if let Some(t) = timezone.from_local_datetime(&local).earliest() {
+                let t = t.with_timezone(&Utc);
+                if t > after {
+                    return Some(t);
+                }
+            }
+        }
+    }
+
+    fn next_local_boundary(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let midnight = after.date().and_hms_opt(0, 0, 0)?;
+        match *self {
+            RotateInterval::Never => None,
+            RotateInterval::Hourly => {
+                Some(after.date().and_hms_opt(after.hour(), 0, 0)? + Duration::hours(1))
+            }
+            RotateInterval::Daily => Some(midnight + Duration::days(1)),
+            RotateInterval::Weekly => {
+                let days = 7 - i64::from(after.weekday().num_days_from_monday());
+                Some(midnight + Duration::days(days))
+            }
+            RotateInterval::Cron(ref c) => c.next_after(after),
+        }
+    }
+
+    fn last_local_boundary(&self, at: NaiveDateTime) -> Option<NaiveDateTime> {
+        let midnight = at.date().and_hms_opt(0, 0, 0)?;
+        match *self {
+            RotateInterval::Never => None,
+            RotateInterval::Hourly => at.date().and_hms_opt(at.hour(), 0, 0),
+            RotateInterval::Daily => Some(midnight),
+            RotateInterval::Weekly => {
+                let days = i64::from(at.weekday().num_days_from_monday());
+                Some(midnight - Duration::days(days))
+            }
+            RotateInterval::Cron(ref c) => c.last_at_or_before(at),
+        }
+    }
 }
-impl Config for FileLoggerConfig {
+impl FromStr for RotateInterval {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "never" => Ok(RotateInterval::Never),
+            "hourly" => Ok(RotateInterval::Hourly),
+            "daily" => Ok(RotateInterval::Daily),
+            "weekly" => Ok(RotateInterval::Weekly),
+            _ => track!(s.parse::<CronSchedule>()).map(RotateInterval::Cron),
+        }
+    }
+}
+
+/// The naming scheme of rotated log files.
+///
+/// For details, see the documentation of [`rotate_naming`].
+///
+/// [`rotate_naming`]: ./struct.FileLoggerBuilder.html#method.rotate_naming
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::file::RotateNaming;
+///
+/// assert_eq!(RotateNaming::default(), RotateNaming::Sequential);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RotateNaming {
+    /// Rotated files are named `"${ORIGINAL_FILE_NAME}.${SEQUENCE_NUMBER}"`.
+    #[default]
+    Sequential,
+
+    /// Rotated files are named `"${ORIGINAL_FILE_NAME}.${TIMESTAMP}"`.
+    Timestamp,
+}
+impl FromStr for RotateNaming {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sequential" => Ok(RotateNaming::Sequential),
+            "timestamp" => Ok(RotateNaming::Timestamp),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined rotate naming: {:?}", s),
+        }
+    }
+}
+
+/// Cron-like schedule.
+///
+/// The expression consists of five fields separated by white spaces:
+/// minute (0-59), hour (0-23), day of month (1-31), month (1-12) and
+/// day of week (0-7, both 0 and 7 are Sunday).
+///
+/// Each field is `*` or a comma separated list of numbers (`5`), ranges (`1-5`) and
+/// steps (`*/15`, `0-30/10` or `10/20`).
+/// As with the traditional cron, if both day of month and day of week are restricted,
+/// a day matching either of them is selected.
+///
+/// # Examples
+///
+/// ```
+/// use sloggers::file::CronSchedule;
+///
+/// let every_morning: CronSchedule = "0 6 * * *".parse().unwrap();
+/// assert_eq!(every_morning.as_str(), "0 6 * * *");
+///
+/// assert!("0 6 * *".parse::<CronSchedule>().is_err());
+/// assert!("60 * * * *".parse::<CronSchedule>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+impl CronSchedule {
+    /// Returns the cron expression of this schedule.
+    pub fn as_str(&self) -> &str {
+        &self.expression
+    }
+
+    fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = after + Duration::days(366 * 5);
+        let mut t =
+            after.date().and_hms_opt(after.hour(), after.minute(), 0)? + Duration::minutes(1);
+        while t <= limit {
+            if !self.matches_date(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn last_at_or_before(&self, at: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = at - Duration::days(366 * 5);
+        let mut t = at.date().and_hms_opt(at.hour(), at.minute(), 0)?;
+        while t >= limit {
+            if !self.matches_date(t.date()) {
+                t = t.date().and_hms_opt(0, 0, 0)? - Duration::minutes(1);
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? - Duration::minutes(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t -= Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day_of_month = self.days_of_month & (1 << date.day()) != 0;
+        let day_of_week = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            (true, false) => day_of_month,
+            (false, true) => day_of_week,
+            (false, false) => true,
+        }
+    }
+
+    fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+        let mut bits = 0;
+        for part in field.split(',') {
+            let (range, step) = match part.find('/') {
+                Some(i) => (&part[..i], Some(&part[i + 1..])),
+                None => (part, None),
+            };
+            let step = match step {
+                Some(step) => track!(parse_number(step))?,
+                None => 1,
+            };
+            let (first, last) = if range == "*" {
+                (min, max)
+            } else if let Some(i) = range.find('-') {
+                (
+                    track!(parse_number(&range[..i]))?,
+                    track!(parse_number(&range[i + 1..]))?,
+                )
+            } else {
+                let first = track!(parse_number(range))?;
+                if step == 1 {
+                    (first, first)
+                } else {
+                    (first, max)
+                }
+            };
+            track_assert!(step > 0, ErrorKind::Invalid, "Zero step: {:?}", field);
+            track_assert!(
+                min <= first && first <= last && last <= max,
+                ErrorKind::Invalid,
+                "Out of range ({}-{}): {:?}",
+                min,
+                max,
+                field
+            );
+            for i in (first..last + 1).step_by(step as usize) {
+                bits |= 1 << i;
+            }
+        }
+        Ok(bits)
+    }
+}
+impl FromStr for CronSchedule {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let fields = s.split_whitespace().collect::<Vec<_>>();
+        track_assert_eq!(
+            fields.len(),
+            5,
+            ErrorKind::Invalid,
+            "Cron expression must have five fields: {:?}",
+            s
+        );
+
+        let mut days_of_week = track!(Self::parse_field(fields[4], 0, 7))?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+        Ok(CronSchedule {
+            expression: s.to_owned(),
+            minutes: track!(Self::parse_field(fields[0], 0, 59))?,
+            hours: track!(Self::parse_field(fields[1], 0, 23))?,
+            days_of_month: track!(Self::parse_field(fields[2], 1, 31))?,
+            months: track!(Self::parse_field(fields[3], 1, 12))?,
+            days_of_week,
+            day_of_month_restricted: !fields[2].starts_with('*'),
+            day_of_week_restricted: !fields[4].starts_with('*'),
+        })
+    }
+}
+impl Serialize for CronSchedule {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.expression)
+    }
+}
+impl<'de> Deserialize<'de> for CronSchedule {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error as DeError;
+
+        let expression = String::deserialize(deserializer)?;
+        expression.parse().map_err(D::Error::custom)
+    }
+}
+
+fn parse_number(s: &str) -> Result<u32> {
+    match s.parse() {
+        Ok(n) => Ok(n),
+        Err(_) => track_panic!(ErrorKind::Invalid, "Not a number: {:?}", s),
+    }
+}
+
+/// The configuration of `FileLoggerBuilder`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileLoggerConfig {
+    /// Log level.
+    #[serde(default)]
+    pub level: Severity,
+
+    /// Per-module log level directives.
+    ///
+    /// This can be a directive string (e.g., `"info,my_crate::db=debug,hyper=warn"`)
+    /// or a table of module paths and severities.
+    /// See the documentation of [`LevelDirectives`] for more details.
+    ///
+    /// [`LevelDirectives`]: ../types/struct.LevelDirectives.html
+    #[serde(default)]
+    pub levels: Option<LevelDirectives>,
+
+    /// Log record format.
+    #[serde(default)]
+    pub format: Format,
+
+    /// Source code location
+    #[serde(default)]
+    pub source_location: SourceLocation,
+
+    /// The key under which the source code location is added to log records.
+    #[serde(default = "default_source_location_key")]
+    pub source_location_key: String,
+
+    /// Metadata fields added to every log record.
+    #[serde(default)]
+    pub metadata: MetadataFields,
+
+    /// Static key/value pairs added to every log record.
+    ///
+    /// `${NAME}` in string values is replaced with the environment variable `NAME`.
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldValue>,
+
+    /// Keys whose values are replaced with `"[REDACTED]"` (compared case-insensitively).
+    #[serde(default)]
+    pub redact_keys: Vec<String>,
+
+    /// Regular expressions whose matches in messages and string values are replaced with `"[REDACTED]"`.
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+
+    /// Rate limit of log records.
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+
+    /// Time Zone.
+    #[serde(default)]
+    pub timezone: TimeZone,
+
+    /// Timestamp format of log records.
+    #[serde(default)]
+    pub timestamp_format: TimestampFormat,
+
+    /// Format string for the timestamp in the path and the names of rotated files.
+    /// The string is formatted using [strftime](https://docs.rs/chrono/0.4.6/chrono/format/strftime/index.html#specifiers)
+    ///
+    /// Default: "%Y%m%d_%H%M", example: "20180918_1127"
+    #[serde(default = "default_timestamp_template")]
+    pub timestamp_template: String,
+
+    /// Log file path template.
+    ///
+    /// It will be used as-is, with the following transformation:
+    ///
+    /// All occurrences of the substring "{timestamp}" will be replaced with the current timestamp
+    /// formatted according to `timestamp_template`. The timestamp will respect the `timezone` setting.
+    pub path: PathBuf,
+
+    /// Asynchronous channel size
+    #[serde(default = "default_channel_size")]
+    pub channel_size: usize,
+
+    /// Whether to process log records synchronously.
+    #[serde(default)]
+    pub sync: bool,
+
+    /// Behavior when the asynchronous channel is full.
+    #[serde(default)]
+    pub overflow_strategy: OverflowStrategy,
+
+    /// Truncate the file or not
+    #[serde(default)]
+    pub truncate: bool,
+
+    /// Log file rotation size.
+    ///
+    /// For details, see the documentation of [`rotate_size`].
+    ///
+    /// [`rotate_size`]: ./struct.FileLoggerBuilder.html#method.rotate_size
+    #[serde(default = "default_rotate_size")]
+    pub rotate_size: u64,
+
+    /// Log file rotation interval.
+    ///
+    /// For details, see the documentation of [`rotate_interval`].
+    ///
+    /// [`rotate_interval`]: ./struct.FileLoggerBuilder.html#method.rotate_interval
+    #[serde(default)]
+    pub rotate_interval: RotateInterval,
+
+    /// Maximum number of rotated log files to keep.
+    ///
+    /// For details, see the documentation of [`rotate_keep`].
+    ///
+    /// [`rotate_keep`]: ./struct.FileLoggerBuilder.html#method.rotate_keep
+    #[serde(default = "default_rotate_keep")]
+    pub rotate_keep: usize,
+
+    /// Naming scheme of rotated log files.
+    ///
+    /// For details, see the documentation of [`rotate_naming`].
+    ///
+    /// [`rotate_naming`]: ./struct.FileLoggerBuilder.html#method.rotate_naming
+    #[serde(default)]
+    pub rotate_naming: RotateNaming,
+
+    /// Whether to compress or not compress rotated files.
+    ///
+    /// For details, see the documentation of [`rotate_keep`].
+    ///
+    /// [`rotate_compress`]: ./struct.FileLoggerBuilder.html#method.rotate_compress
+    ///
+    /// The default value is `false`.
+    #[serde(default)]
+    pub rotate_compress: bool,
+
+    /// [`KVFilter`] parameters.
+    ///
+    /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
+    #[serde(default)]
+    pub kvfilter: Option<KVFilterParameters>,
+}
+impl Config for FileLoggerConfig {
     type Builder = FileLoggerBuilder;
     fn try_to_builder(&self) -> Result<Self::Builder> {
         let now = Utc::now();
@@ -436,16 +1346,43 @@ impl Config for FileLoggerConfig {
             path_template_to_path(path_template, &self.timestamp_template, self.timezone, now);
         let mut builder = FileLoggerBuilder::new(&path);
         builder.level(self.level);
-        builder.format(self.format);
+        if let Some(ref d) = self.levels {
+            builder.level_directives(d.clone());
+        }
+        builder.format(self.format.clone());
         builder.source_location(self.source_location);
+        builder.source_location_key(&self.source_location_key);
+        builder.metadata(self.metadata.clone());
+        for (key, value) in &self.fields {
+            builder.field(key, track!(value.expand_env(), "key={:?}", key)?);
+        }
+        for key in &self.redact_keys {
+            builder.redact_key(key);
+        }
+        for pattern in &self.redact_patterns {
+            let pattern = track!(Regex::new(pattern).map_err(|e| ErrorKind::Invalid.cause(e)))?;
+            builder.redact_pattern(pattern);
+        }
+        if let Some(limit) = self.rate_limit {
+            builder.rate_limit(limit);
+        }
         builder.timezone(self.timezone);
+        builder.timestamp_format(self.timestamp_format.clone());
         builder.channel_size(self.channel_size);
+        builder.sync(self.sync);
+        builder.overflow_strategy(self.overflow_strategy);
         builder.rotate_size(self.rotate_size);
+        builder.rotate_interval(self.rotate_interval.clone());
         builder.rotate_keep(self.rotate_keep);
+        builder.rotate_naming(self.rotate_naming);
+        builder.timestamp_template(&self.timestamp_template);
         builder.rotate_compress(self.rotate_compress);
         if self.truncate {
             builder.truncate();
         }
+        if let Some(ref p) = self.kvfilter {
+            builder.kvfilter(p.clone());
+        }
         Ok(builder)
     }
 }
@@ -453,16 +1390,29 @@ impl Default for FileLoggerConfig {
     fn default() -> Self {
         FileLoggerConfig {
             level: Severity::default(),
+            levels: None,
             format: Format::default(),
             source_location: SourceLocation::default(),
+            source_location_key: default_source_location_key(),
+            metadata: MetadataFields::default(),
+            fields: BTreeMap::new(),
+            redact_keys: Vec::new(),
+            redact_patterns: Vec::new(),
+            rate_limit: None,
             timezone: TimeZone::default(),
+            timestamp_format: TimestampFormat::default(),
             path: PathBuf::default(),
             timestamp_template: default_timestamp_template(),
             channel_size: default_channel_size(),
+            sync: false,
+            overflow_strategy: OverflowStrategy::default(),
             truncate: false,
             rotate_size: default_rotate_size(),
+            rotate_interval: RotateInterval::default(),
             rotate_keep: default_rotate_keep(),
+            rotate_naming: RotateNaming::default(),
             rotate_compress: false,
+            kvfilter: None,
         }
     }
 }
@@ -473,24 +1423,30 @@ fn path_template_to_path(
     timezone: TimeZone,
     date_time: DateTime<Utc>,
 ) -> PathBuf {
-    let timestamp_string = match timezone {
-        TimeZone::Local => {
-            let local_timestamp = Local.from_utc_datetime(&date_time.naive_utc());
-            local_timestamp.format(&timestamp_template)
-        }
-        TimeZone::Utc => date_time.format(&timestamp_template),
-    }.to_string();
+    let timestamp_string = format_timestamp(timestamp_template, timezone, date_time);
     let path_string = path_template.replace("{timestamp}", &timestamp_string);
     PathBuf::from(path_string)
 }
 
+// Rotated files are ordered by the date and time in their names (fields which
+// the timestamp template does not contain are `None`), then by their sequence numbers
+type RotatedFileTimestamp = (Option<NaiveDate>, Option<NaiveTime>, u64);
+
+fn parse_timestamp(s: &str, template: &str) -> Option<(Option<NaiveDate>, Option<NaiveTime>)> {
+    let mut parsed = Parsed::new();
+    chrono_format::parse(&mut parsed, s, StrftimeItems::new(template)).ok()?;
+    Some((parsed.to_naive_date().ok(), parsed.to_naive_time().ok()))
+}
+
+fn default_source_location_key() -> String {
+    "module".to_owned()
+}
+
 fn default_channel_size() -> usize {
     1024
 }
 
 fn default_rotate_size() -> u64 {
-    use std::u64;
-
     u64::MAX
 }
 
@@ -504,13 +1460,15 @@ fn default_timestamp_template() -> String {
 
 #[cfg(test)]
 mod tests {
-    use chrono::NaiveDateTime;
+    use std::env;
+    use std::process;
     use std::thread;
     use std::time::Duration;
     use tempdir::TempDir;
 
     use super::*;
-    use {Build, ErrorKind, Result};
+    use test_util::lock_global_state;
+    use {Build, Config, ErrorKind, Result};
 
     #[test]
     fn file_rotation_works() -> Result<()> {
@@ -585,6 +1543,740 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn file_timestamp_rotation_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let mut appender = FileAppender::new(dir.path().join("foo.log"));
+        appender.rotate_size = 1;
+        appender.rotate_keep = 2;
+        appender.rotate_naming = RotateNaming::Timestamp;
+        appender.timestamp_template = "%Y%m%d".to_owned();
+        appender.timezone = TimeZone::Utc;
+        fs::write(dir.path().join("foo.log.bar"), b"unrelated\n")?;
+
+        let today = Utc::now().format("%Y%m%d").to_string();
+        let rotated = |suffix: &str| dir.path().join(format!("foo.log.{}{}", today, suffix));
+
+        appender.write_all(b"hello\n")?;
+        appender.flush()?;
+        assert!(rotated("").exists());
+        assert!(!rotated(".1").exists());
+
+        appender.write_all(b"world\n")?;
+        appender.flush()?;
+        assert!(rotated("").exists());
+        assert!(rotated(".1").exists());
+
+        appender.write_all(b"!\n")?;
+        appender.flush()?;
+        assert!(!rotated("").exists());
+        assert!(rotated(".1").exists());
+        assert!(rotated(".2").exists());
+        assert!(dir.path().join("foo.log.bar").exists());
+        assert!(!dir.path().join("foo.log.1").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_timestamp_interval_rotation_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let mut appender = FileAppender::new(dir.path().join("foo.log"));
+        appender.rotate_keep = 2;
+        appender.rotate_interval = RotateInterval::Hourly;
+        appender.rotate_naming = RotateNaming::Timestamp;
+        appender.timestamp_template = "%Y%m%d%H".to_owned();
+        appender.timezone = TimeZone::Utc;
+
+        // Older by name, but newer by modification time
+        fs::write(dir.path().join("foo.log.2018091812"), b"foo\n")?;
+        fs::write(dir.path().join("foo.log.2018091811"), b"bar\n")?;
+
+        appender.write_all(b"hello\n")?;
+        appender.flush()?;
+        appender.interval_start = Some("2018-09-18T13:00:00Z".parse().unwrap());
+        appender.next_rotation = Some(Utc::now() - ::chrono::Duration::seconds(1));
+        appender.write_all(b"world\n")?;
+        appender.flush()?;
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("foo.log.2018091813"))?,
+            "hello\n"
+        );
+        assert!(dir.path().join("foo.log.2018091812").exists());
+        assert!(!dir.path().join("foo.log.2018091811").exists());
+        assert_eq!(fs::read_to_string(dir.path().join("foo.log"))?, "world\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_logger_guard_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let (logger, guard) = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .rotate_size(1)
+            .rotate_compress(true)
+            .build_with_guard()?;
+
+        info!(logger, "hello");
+        info!(logger, "world");
+        drop(guard);
+        assert!(dir.path().join("foo.log.1.gz").exists());
+        assert!(!dir.path().join("foo.log.1.gz.temp").exists());
+
+        // Records emitted after the guard is dropped are discarded
+        info!(logger, "foo");
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_json_format_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .format(Format::Json)
+            .timezone(TimeZone::Utc)
+            .build()?;
+
+        info!(logger, "hello"; "foo" => 10);
+        crit!(logger, "bye");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.starts_with("{"));
+        assert!(content.contains("\"ts\":\""));
+        assert!(content.contains("\"level\":\"info\""));
+        assert!(content.contains("\"level\":\"critical\""));
+        assert!(content.contains("\"msg\":\"hello\""));
+        assert!(content.contains("\"module\":"));
+        assert!(content.contains("\"foo\":10"));
+        assert!(content.ends_with("}\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_source_location_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let path = dir.path().join("foo.log");
+        let build = |location| {
+            FileLoggerBuilder::new(&path)
+                .format(Format::Json)
+                .source_location(location)
+                .source_location_key("loc")
+                .build()
+        };
+
+        let logger = build(SourceLocation::FileAndLine)?;
+        info!(logger, "foo");
+        let logger = build(SourceLocation::FullPathAndLine)?;
+        info!(logger, "bar");
+        let logger = build(SourceLocation::ModuleFileAndLine)?;
+        info!(logger, "baz");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(&path)?;
+        assert!(!content.contains("\"module\":"));
+        assert!(content.contains("\"loc\":\"file.rs:"));
+        assert!(content.contains("\"loc\":\"src/file.rs:"));
+        assert!(content.contains("\"loc\":\"sloggers::file::tests src/file.rs:"));
+
+        assert_eq!(
+            "full_path_and_line".parse::<SourceLocation>().ok(),
+            Some(SourceLocation::FullPathAndLine)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_metadata_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .format(Format::Json)
+            .metadata(MetadataFields {
+                pid: true,
+                thread_id: true,
+                thread_name: true,
+                app_name: Some("my-app".to_owned()),
+                app_version: Some("1.2.3".to_owned()),
+                ..MetadataFields::default()
+            })
+            .build()?;
+
+        thread::Builder::new()
+            .name("worker".to_owned())
+            .spawn(move || info!(logger, "hello"))?
+            .join()
+            .expect("Cannot join the logging thread");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.contains(&format!("\"pid\":{}", process::id())));
+        assert!(content.contains("\"thread_id\":"));
+        assert!(content.contains("\"thread\":\"worker\""));
+        assert!(content.contains("\"app_name\":\"my-app\""));
+        assert!(content.contains("\"app_version\":\"1.2.3\""));
+        assert!(!content.contains("\"executable\":"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_static_fields_work() -> Result<()> {
+        let _lock = lock_global_state();
+        let dir = TempDir::new("sloggers_test")?;
+        let mut config = FileLoggerConfig {
+            path: dir.path().join("foo.log"),
+            format: Format::Json,
+            ..FileLoggerConfig::default()
+        };
+        config
+            .fields
+            .insert("dc".to_owned(), "${SLOGGERS_TEST_DC}-1".into());
+        config.fields.insert("shard".to_owned(), 3.into());
+        config.fields.insert("canary".to_owned(), true.into());
+
+        env::set_var("SLOGGERS_TEST_DC", "tokyo");
+        let logger = config.build_logger()?;
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.contains("\"dc\":\"tokyo-1\""));
+        assert!(content.contains("\"shard\":3"));
+        assert!(content.contains("\"canary\":true"));
+
+        env::remove_var("SLOGGERS_TEST_DC");
+        assert!(config.build_logger().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_redaction_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .format(Format::Json)
+            .field("token", "t0p")
+            .redact_key("password")
+            .redact_key("TOKEN")
+            .redact_pattern(Regex::new(r"Bearer [A-Za-z0-9._-]+").unwrap())
+            .build()?;
+
+        info!(logger, "Got Bearer abc"; "password" => "secret", "auth" => "Bearer xyz", "count" => 3);
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(!content.contains("t0p"));
+        assert!(!content.contains("secret"));
+        assert!(!content.contains("abc"));
+        assert!(!content.contains("xyz"));
+        assert!(content.contains("\"msg\":\"Got [REDACTED]\""));
+        assert!(content.contains("\"token\":\"[REDACTED]\""));
+        assert!(content.contains("\"password\":\"[REDACTED]\""));
+        assert!(content.contains("\"auth\":\"[REDACTED]\""));
+        assert!(content.contains("\"count\":3"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_rate_limit_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .rate_limit(RateLimit {
+                rate: 20.0,
+                burst: 2,
+            })
+            .build()?;
+
+        let log = |i| info!(logger, "hello"; "i" => i);
+        for i in 0..5 {
+            log(i);
+        }
+        thread::sleep(Duration::from_millis(100));
+        log(5);
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.contains("i: 0"));
+        assert!(content.contains("i: 1"));
+        assert!(!content.contains("i: 2"));
+        assert!(!content.contains("i: 4"));
+        assert!(content.contains("Suppressed 3 similar messages"));
+        assert!(content.contains("i: 5"));
+
+        let invalid = FileLoggerBuilder::new(dir.path().join("bar.log"))
+            .rate_limit(RateLimit {
+                rate: 0.0,
+                burst: 2,
+            })
+            .build();
+        assert_eq!(
+            invalid.err().map(|e| e.kind().clone()),
+            Some(ErrorKind::Invalid)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_rate_limit_summary_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .sync(true)
+            .rate_limit(RateLimit {
+                rate: 20.0,
+                burst: 1,
+            })
+            .build()?;
+
+        // The summary is outputted once the window closes, even if no record follows
+        let log = || info!(logger, "hello");
+        for _ in 0..3 {
+            log();
+        }
+        thread::sleep(Duration::from_millis(200));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.contains("Suppressed 2 similar messages, suppressed: 2"));
+
+        // Records are rate limited per message template, even if they are emitted by different statements
+        info!(logger, "world");
+        info!(logger, "world");
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert_eq!(content.matches("world").count(), 1);
+
+        // The summaries of the open windows are outputted when the logger is dropped
+        let logger = FileLoggerBuilder::new(dir.path().join("bar.log"))
+            .sync(true)
+            .rate_limit(RateLimit {
+                rate: 0.001,
+                burst: 1,
+            })
+            .build()?;
+        for _ in 0..3 {
+            info!(logger, "hello");
+        }
+        drop(logger);
+
+        let content = fs::read_to_string(dir.path().join("bar.log"))?;
+        assert!(content.contains("Suppressed 2 similar messages"));
+
+        // The summaries are also outputted when the guard is dropped
+        let (logger, guard) = FileLoggerBuilder::new(dir.path().join("baz.log"))
+            .rate_limit(RateLimit {
+                rate: 0.001,
+                burst: 1,
+            })
+            .build_with_guard()?;
+        for _ in 0..3 {
+            info!(logger, "hello");
+        }
+        drop(guard);
+
+        let content = fs::read_to_string(dir.path().join("baz.log"))?;
+        assert!(content.contains("Suppressed 2 similar messages"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_rate_limit_ignores_filtered_out_records() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let (logger, handle) = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .sync(true)
+            .level(Severity::Info)
+            .rate_limit(RateLimit {
+                rate: 0.001,
+                burst: 1,
+            })
+            .build_with_level_handle()?;
+
+        let log = |i| {
+            debug!(logger, "foo"; "i" => i);
+            error!(logger, "bar"; "i" => i);
+        };
+        for i in 0..5 {
+            log(i);
+        }
+        handle.set(Severity::Debug);
+        log(5);
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.contains("bar, i: 0"));
+        assert!(!content.contains("foo, i: 4"));
+        assert!(content.contains("foo, i: 5"));
+        assert!(!content.contains("bar, i: 5"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_logfmt_format_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .format(Format::Logfmt)
+            .timezone(TimeZone::Utc)
+            .build()?;
+
+        info!(logger, "hello world";
+              "foo" => 10, "bar" => "a=\"b\"\n", "baz" => "", "qux key" => "c\\d");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.starts_with("ts="));
+        assert!(content.contains(" level=info msg=\"hello world\" module=sloggers::file::tests:"));
+        assert!(content.contains(" foo=10"));
+        assert!(content.contains(r#" bar="a=\"b\"\n""#));
+        assert!(content.contains(r#" baz="""#));
+        assert!(content.contains(r#" qux_key="c\\d""#));
+        assert!(content.ends_with("\n"));
+        assert_eq!(content.lines().count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_template_format_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .format(Format::Template(
+                "[{level_long}] {module}:{line} <{thread}> {msg} ({key:foo}) {kv}".to_owned(),
+            ))
+            .build()?;
+
+        let handle = thread::Builder::new()
+            .name("qux".to_owned())
+            .spawn(move || info!(logger, "hello"; "foo" => 10, "bar" => "baz"))?;
+        let _ = handle.join();
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.starts_with("[INFO] sloggers::file::tests:"));
+        assert!(content.ends_with(" <qux> hello (10) bar=baz\n"));
+
+        let result = FileLoggerBuilder::new(dir.path().join("bar.log"))
+            .format(Format::Template("{unknown}".to_owned()))
+            .build();
+        assert_eq!(
+            result.err().map(|e| e.kind().clone()),
+            Some(ErrorKind::Invalid)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_timestamp_format_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .format(Format::Template("{ts}".to_owned()))
+            .timezone(TimeZone::Utc)
+            .timestamp_format(TimestampFormat::Rfc3339Millis)
+            .build()?;
+        info!(logger, "foo");
+        thread::sleep(Duration::from_millis(50));
+
+        // e.g., "2018-09-18T01:19:51.093+00:00"
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        let timestamp = content.trim_end();
+        assert_eq!(timestamp.len(), 29);
+        assert!(DateTime::parse_from_rfc3339(timestamp).is_ok());
+        assert!(timestamp.ends_with("+00:00"));
+
+        let logger = FileLoggerBuilder::new(dir.path().join("bar.log"))
+            .format(Format::Json)
+            .timestamp_format(TimestampFormat::UnixSecs)
+            .build()?;
+        info!(logger, "bar");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("bar.log"))?;
+        let ts = content["{\"ts\":".len()..].split(',').next().unwrap_or("");
+        let ts = ts.parse::<i64>().ok();
+        assert!(ts.map_or(false, |t| (Utc::now().timestamp() - t).abs() < 10));
+
+        let logger = FileLoggerBuilder::new(dir.path().join("baz.log"))
+            .format(Format::Logfmt)
+            .timestamp_format(TimestampFormat::None)
+            .build()?;
+        info!(logger, "baz");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("baz.log"))?;
+        assert!(content.starts_with("level=info msg=baz"));
+
+        let result = FileLoggerBuilder::new(dir.path().join("qux.log"))
+            .timestamp_format(TimestampFormat::Strftime("%Q".to_owned()))
+            .build();
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_level_handle_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let (logger, handle) = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .level(Severity::Info)
+            .build_with_level_handle()?;
+
+        debug!(logger, "foo");
+        handle.clone().set(Severity::Debug);
+        debug!(logger, "bar");
+        handle.set(Severity::Error);
+        warn!(logger, "baz");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(!content.contains("foo"));
+        assert!(content.contains("bar"));
+        assert!(!content.contains("baz"));
+        assert_eq!(handle.get(), Severity::Error);
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_level_directives_work() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let (logger, handle) = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .level(Severity::Info)
+            .level_directives(track!(
+                "warning,sloggers::file=debug,sloggers::f=error".parse()
+            )?)
+            .build_with_level_handle()?;
+        assert_eq!(handle.get(), Severity::Warning);
+
+        debug!(logger, "foo");
+        handle.set(Severity::Critical);
+        debug!(logger, "bar");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.contains("foo"));
+        assert!(content.contains("bar"));
+
+        let logger = FileLoggerBuilder::new(dir.path().join("bar.log"))
+            .level(Severity::Debug)
+            .level_directives(track!("sloggers::file::tests=error".parse())?)
+            .build()?;
+        warn!(logger, "baz");
+        error!(logger, "qux");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("bar.log"))?;
+        assert!(!content.contains("baz"));
+        assert!(content.contains("qux"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_level_handle_syncs_stdlog_max_level() -> Result<()> {
+        use log;
+
+        let _lock = lock_global_state();
+        let dir = TempDir::new("sloggers_test")?;
+        let (_logger, handle) = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .level(Severity::Warning)
+            .level_directives(track!("sloggers::file=info".parse())?)
+            .build_with_level_handle()?;
+        handle.sync_stdlog_max_level();
+        assert_eq!(log::max_level(), log::LevelFilter::Info);
+
+        handle.set(Severity::Debug);
+        assert_eq!(log::max_level(), log::LevelFilter::Debug);
+
+        handle.set_stdlog_severity(Some(Severity::Error));
+        assert_eq!(log::max_level(), log::LevelFilter::Error);
+
+        handle.set_stdlog_severity(None);
+        handle.set(Severity::Critical);
+        assert_eq!(log::max_level(), log::LevelFilter::Info);
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_overflow_strategy_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let mut builder = FileLoggerBuilder::new(dir.path().join("foo.log"));
+        builder.channel_size(1);
+        builder.overflow_strategy(OverflowStrategy::DropAndReport);
+        let counter = builder.dropped_record_counter();
+
+        let logger = builder.build()?;
+        for i in 0..1000 {
+            info!(logger, "record {}", i);
+        }
+        drop(logger);
+        assert!(counter.get() > 0);
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.contains("log records were dropped due to channel overflow"));
+        assert!(content.lines().count() < 1000);
+
+        let mut builder = FileLoggerBuilder::new(dir.path().join("bar.log"));
+        builder.channel_size(1);
+        builder.overflow_strategy(OverflowStrategy::Block);
+        let counter = builder.dropped_record_counter();
+
+        let logger = builder.build()?;
+        for i in 0..1000 {
+            info!(logger, "record {}", i);
+        }
+        drop(logger);
+        assert_eq!(counter.get(), 0);
+
+        let content = fs::read_to_string(dir.path().join("bar.log"))?;
+        assert_eq!(content.lines().count(), 1000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_sync_mode_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .sync(true)
+            .build()?;
+
+        info!(logger, "hello");
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.contains("hello"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_interval_rotation_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let mut appender = FileAppender::new(dir.path().join("foo.log"));
+        appender.rotate_interval = RotateInterval::Hourly;
+
+        appender.write_all(b"hello\n")?;
+        appender.flush()?;
+        assert!(appender.next_rotation.is_some());
+        assert!(!dir.path().join("foo.log.1").exists());
+
+        appender.next_rotation = Some(Utc::now() - ::chrono::Duration::seconds(1));
+        appender.write_all(b"world\n")?;
+        appender.flush()?;
+        assert_eq!(fs::read_to_string(dir.path().join("foo.log.1"))?, "hello\n");
+        assert_eq!(fs::read_to_string(dir.path().join("foo.log"))?, "world\n");
+        assert!(appender.next_rotation.map_or(false, |t| t > Utc::now()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_rotation_is_deferred_during_compression() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let mut appender = FileAppender::new(dir.path().join("foo.log"));
+        appender.rotate_size = 1;
+        appender.rotate_compress = true;
+
+        appender.write_all(b"a\n")?;
+        appender.flush()?;
+        while appender.is_compressing() {
+            thread::sleep(::std::time::Duration::from_millis(1));
+        }
+        assert!(dir.path().join("foo.log.1.gz").exists());
+
+        // Simulates an in-flight compression
+        let (tx, rx) = mpsc::channel();
+        *appender
+            .wait_compression
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(rx);
+        appender.write_all(b"b\n")?;
+        appender.flush()?;
+        assert_eq!(fs::read_to_string(dir.path().join("foo.log"))?, "b\n");
+        assert!(!dir.path().join("foo.log.2.gz").exists());
+
+        // The error of the compression does not fail the writing
+        let _ = tx.send(Err(io::Error::new(io::ErrorKind::Other, "foo")));
+        appender.write_all(b"c\n")?;
+        appender.flush()?;
+        assert!(dir.path().join("foo.log.2.gz").exists());
+        assert_eq!(appender.compression_errors.lock().unwrap().len(), 1);
+        while appender.is_compressing() {
+            thread::sleep(::std::time::Duration::from_millis(1));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rotate_interval_boundaries_work() -> Result<()> {
+        fn next(interval: &str) -> Result<Option<DateTime<Utc>>> {
+            // Tuesday
+            let after = "2018-09-18T10:19:51Z".parse().unwrap();
+            let interval: RotateInterval = track!(interval.parse())?;
+            Ok(interval.next_rotation(after, TimeZone::Utc))
+        }
+        fn utc(s: &str) -> Option<DateTime<Utc>> {
+            Some(s.parse().unwrap())
+        }
+
+        assert_eq!(next("never")?, None);
+        assert_eq!(next("hourly")?, utc("2018-09-18T11:00:00Z"));
+        assert_eq!(next("daily")?, utc("2018-09-19T00:00:00Z"));
+        assert_eq!(next("weekly")?, utc("2018-09-24T00:00:00Z"));
+        assert_eq!(next("*/15 * * * *")?, utc("2018-09-18T10:30:00Z"));
+        assert_eq!(next("30 4 * * 1-5")?, utc("2018-09-19T04:30:00Z"));
+        assert_eq!(next("0 0 1 * *")?, utc("2018-10-01T00:00:00Z"));
+        assert_eq!(next("0 0 1 * 0")?, utc("2018-09-23T00:00:00Z"));
+        assert_eq!(next("0 0 29 2 *")?, utc("2020-02-29T00:00:00Z"));
+        assert_eq!(next("0 0 30 2 *")?, None);
+
+        fn start(interval: &str) -> Result<Option<DateTime<Utc>>> {
+            let at = "2018-09-18T10:19:51Z".parse().unwrap();
+            let interval: RotateInterval = track!(interval.parse())?;
+            Ok(interval.interval_start(at, TimeZone::Utc))
+        }
+        assert_eq!(start("never")?, None);
+        assert_eq!(start("hourly")?, utc("2018-09-18T10:00:00Z"));
+        assert_eq!(start("daily")?, utc("2018-09-18T00:00:00Z"));
+        assert_eq!(start("weekly")?, utc("2018-09-17T00:00:00Z"));
+        assert_eq!(start("*/15 * * * *")?, utc("2018-09-18T10:15:00Z"));
+        assert_eq!(start("30 4 * * 1-5")?, utc("2018-09-18T04:30:00Z"));
+        assert_eq!(start("0 0 1 * *")?, utc("2018-09-01T00:00:00Z"));
+        assert_eq!(start("0 0 30 2 *")?, None);
+
+        assert!("* * * *".parse::<CronSchedule>().is_err());
+        assert!("* 24 * * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+        assert!("5-1 * * * *".parse::<CronSchedule>().is_err());
+        assert!("a * * * *".parse::<CronSchedule>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rotate_interval_named_time_zone_works() -> Result<()> {
+        let timezone: TimeZone = track!("Europe/Prague".parse())?;
+        let daily = RotateInterval::Daily;
+
+        // CEST (+02:00)
+        let after = "2018-09-18T10:19:51Z".parse().unwrap();
+        let expected = "2018-09-18T22:00:00Z".parse().unwrap();
+        assert_eq!(daily.next_rotation(after, timezone), Some(expected));
+        let expected = "2018-09-17T22:00:00Z".parse().unwrap();
+        assert_eq!(daily.interval_start(after, timezone), Some(expected));
+
+        // The day on which DST ends is 25 hours long
+        let after = "2018-10-27T22:00:00Z".parse().unwrap();
+        let expected = "2018-10-28T23:00:00Z".parse().unwrap();
+        assert_eq!(daily.next_rotation(after, timezone), Some(expected));
+
+        Ok(())
+    }
+
     #[test]
     fn test_path_template_to_path() -> Result<()> {
         let dir = TempDir::new("sloggers_test")?;
@@ -598,11 +2290,20 @@ mod tests {
             &path_template,
             "%Y%m%d_%H%M",
             TimeZone::Utc, // Local is difficult to test, omitting :(
-            Utc.from_utc_datetime(&NaiveDateTime::from_timestamp(1537265991, 0)),
+            "2018-09-18T10:19:51Z".parse().unwrap(),
         );
         let expected = dir.path().join("foo_20180918_1019.log");
         assert_eq!(expected, actual);
 
+        let date_time = "2018-09-18T10:19:51Z".parse().unwrap();
+        let timezone = track!("+09:00".parse())?;
+        let actual = path_template_to_path(&path_template, "%Y%m%d_%H%M", timezone, date_time);
+        assert_eq!(dir.path().join("foo_20180918_1919.log"), actual);
+
+        let timezone = track!("Europe/Prague".parse())?;
+        let actual = path_template_to_path(&path_template, "%Y%m%d_%H%M", timezone, date_time);
+        assert_eq!(dir.path().join("foo_20180918_1219.log"), actual);
+
         Ok(())
     }
 }
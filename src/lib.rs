@@ -47,13 +47,19 @@
 //! ```
 #![warn(missing_docs)]
 extern crate chrono;
+extern crate chrono_tz;
+extern crate hostname;
 extern crate libflate;
+extern crate log;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[cfg(test)]
+extern crate serdeconv;
 #[macro_use]
 extern crate slog;
 extern crate slog_async;
+extern crate slog_json;
 extern crate slog_kvfilter;
 extern crate slog_scope;
 extern crate slog_stdlog;
@@ -63,21 +69,51 @@ extern crate tempdir;
 #[macro_use]
 extern crate trackable;
 extern crate regex;
+#[cfg(feature = "reloadable")]
+extern crate toml;
+#[cfg(all(test, feature = "tracing"))]
+extern crate tracing;
+#[cfg(feature = "tracing")]
+extern crate tracing_core;
+#[cfg(feature = "tracing")]
+extern crate tracing_subscriber;
 
-pub use build::{Build, LoggerBuilder};
+pub use build::{Build, LoggerBuilder, LoggerGuard};
 pub use config::{Config, LoggerConfig};
 pub use error::{Error, ErrorKind};
-pub use misc::set_stdlog_logger;
+pub use misc::{init, set_stdlog_logger, GlobalGuard};
 
 pub mod file;
+pub mod multi;
 pub mod null;
+#[cfg(feature = "reloadable")]
+pub mod reloadable;
+pub mod syslog;
 pub mod terminal;
+#[cfg(feature = "tracing")]
+pub mod tracing_bridge;
 pub mod types;
 
 mod build;
 mod config;
 mod error;
 mod misc;
+mod rate_limit;
+mod redact;
+mod template;
 
 /// A specialized `Result` type for this crate.
 pub type Result<T> = ::std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod test_util {
+    use std::sync::{Mutex, MutexGuard};
+
+    static GLOBAL_STATE: Mutex<()> = Mutex::new(());
+
+    /// Serializes the tests which modify process-wide state
+    /// (e.g., environment variables and the max level of the `log` crate).
+    pub fn lock_global_state() -> MutexGuard<'static, ()> {
+        GLOBAL_STATE.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
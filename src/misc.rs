@@ -1,26 +1,943 @@
-use slog::{Logger, Record};
-use slog_scope;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use hostname;
+use log;
+use slog::{
+    self, BorrowedKV, Drain, FnValue, Fuse, Key, Level, Logger, Never, OwnedKV, OwnedKVList,
+    PushFnValue, Record, RecordLocation, RecordStatic, KV,
+};
+use slog_async::{AsyncCore, AsyncError, AsyncGuard};
+
+use slog_json::Json;
+use slog_scope::{self, GlobalLoggerGuard};
 use slog_stdlog;
 use slog_term;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt;
 use std::io;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 use trackable::error::ErrorKindExt;
 
-use types::TimeZone;
+use build::LoggerGuard;
+use config::{Config, LoggerConfig};
+use template::THREAD_KEY;
+use types::{DroppedRecordCounter, FieldValue, LevelHandle, MetadataFields, OverflowStrategy};
+use types::{Severity, SourceLocation, TimeZone, TimestampFormat};
 use {ErrorKind, Result};
 
 /// Sets the logger for the log records emitted via `log` crate.
+///
+/// Use [`init`] to keep the logger installed as the global logger of `slog_scope`
+/// while a guard is alive.
+///
+/// The max level of `log` crate is set to the most verbose level enabled by the logger.
+/// Use [`LevelHandle::sync_stdlog_max_level`] to keep it updated when the level is changed later.
+///
+/// [`init`]: ./fn.init.html
+/// [`LevelHandle::sync_stdlog_max_level`]: ./types/struct.LevelHandle.html#method.sync_stdlog_max_level
 pub fn set_stdlog_logger(logger: Logger) -> Result<()> {
+    let max_level = [
+        Severity::Trace,
+        Severity::Debug,
+        Severity::Info,
+        Severity::Warning,
+        Severity::Error,
+        Severity::Critical,
+    ]
+    .iter()
+    .find(|s| logger.is_enabled(s.as_level()))
+    .map_or(log::LevelFilter::Off, |s| s.as_log_level_filter());
+
     let _guard = slog_scope::set_global_logger(logger);
-    track!(slog_stdlog::init().map_err(|e| ErrorKind::Other.cause(e).into()))
+    track!(slog_stdlog::init().map_err(|e| ErrorKind::Other.cause(e)))?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+/// Builds a logger from `config` and installs it as the global logger.
+///
+/// The logger is installed as the global logger of `slog_scope`,
+/// and the log records emitted via `log` crate are also outputted by the logger.
+///
+/// The returned guard must be kept alive while the logger is used.
+/// When the guard is dropped, the global logger is reset and the logger is finalized
+/// (see [`Build::build_with_guard`]).
+///
+/// If the `tracing` feature is enabled, the events emitted via `tracing` crate are also outputted
+/// by the logger (see [`tracing_bridge::set_global_default`]).
+/// Note that they are still passed to the logger after the guard is dropped.
+/// If a global default subscriber of `tracing` has already been set, it is kept as it is.
+///
+/// The max level of `log` crate follows the log level of the logger,
+/// which can be changed at runtime via [`GlobalGuard::level_handle`].
+///
+/// [`Build::build_with_guard`]: ./trait.Build.html#method.build_with_guard
+/// [`GlobalGuard::level_handle`]: ./struct.GlobalGuard.html#method.level_handle
+/// [`tracing_bridge::set_global_default`]: ./tracing_bridge/fn.set_global_default.html
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate log;
+/// extern crate slog_scope;
+/// extern crate sloggers;
+///
+/// use sloggers::LoggerConfig;
+/// use sloggers::types::Severity;
+///
+/// # fn main() {
+/// let guard = sloggers::init(&LoggerConfig::default()).unwrap();
+///
+/// let _logger = slog_scope::logger(); // The global logger
+/// info!("Hello World!"); // Outputted via the global logger
+///
+/// // Suppresses the records emitted via `log` crate except for errors
+/// guard.level_handle().set_stdlog_severity(Some(Severity::Error));
+/// warn!("This record is discarded");
+/// # }
+/// ```
+pub fn init(config: &LoggerConfig) -> Result<GlobalGuard> {
+    let builder = track!(config.try_to_builder())?;
+    let mut logger_guard = LoggerGuard::new();
+    let (logger, level) = track!(builder.build_logger(Some(&mut logger_guard)))?;
+    #[cfg(feature = "tracing")]
+    {
+        // A subscriber which has already been set (e.g., by the application) takes precedence
+        let _ = ::tracing_bridge::set_global_default(logger.clone());
+    }
+    let scope_guard = slog_scope::set_global_logger(logger);
+    track!(slog_stdlog::init().map_err(|e| ErrorKind::Other.cause(e)))?;
+    level.sync_stdlog_max_level();
+    Ok(GlobalGuard {
+        level,
+        _scope_guard: scope_guard,
+        _logger_guard: logger_guard,
+    })
+}
+
+/// A guard which keeps the global logger installed by [`init`].
+///
+/// [`init`]: ./fn.init.html
+#[must_use]
+pub struct GlobalGuard {
+    level: LevelHandle,
+
+    // NOTE: The global logger is reset before the logger is finalized
+    _scope_guard: GlobalLoggerGuard,
+    _logger_guard: LoggerGuard,
+}
+impl GlobalGuard {
+    /// Returns the handle for changing the log level of the global logger.
+    ///
+    /// Changes made via the handle are also applied to the max level of `log` crate.
+    pub fn level_handle(&self) -> &LevelHandle {
+        &self.level
+    }
+}
+impl fmt::Debug for GlobalGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "GlobalGuard {{ level: {:?}, .. }}", self.level)
+    }
 }
 
 pub fn module_and_line(record: &Record) -> String {
     format!("{}:{}", record.module(), record.line())
 }
 
-pub fn timezone_to_timestamp_fn(timezone: TimeZone) -> fn(&mut io::Write) -> io::Result<()> {
+/// Formats the source code location of `record` in the way specified by `location`.
+pub fn format_source_location(record: &Record, location: SourceLocation) -> String {
+    match location {
+        SourceLocation::None => String::new(),
+        SourceLocation::ModuleAndLine => module_and_line(record),
+        SourceLocation::FileAndLine => {
+            let file = Path::new(record.file())
+                .file_name()
+                .map_or_else(|| record.file().into(), |name| name.to_string_lossy());
+            format!("{}:{}", file, record.line())
+        }
+        SourceLocation::FullPathAndLine => format!("{}:{}", record.file(), record.line()),
+        SourceLocation::ModuleFileAndLine => {
+            format!("{} {}:{}", record.module(), record.file(), record.line())
+        }
+        SourceLocation::Function if record.function().is_empty() => record.module().to_owned(),
+        SourceLocation::Function => record.function().to_owned(),
+    }
+}
+
+/// Makes a root logger which adds the source code location of each record under `key`.
+pub fn source_location_logger<D>(drain: D, location: SourceLocation, key: &str) -> Logger
+where
+    D: Drain<Ok = (), Err = Never> + Send + Sync + RefUnwindSafe + UnwindSafe + 'static,
+{
+    if location == SourceLocation::None {
+        return Logger::root(drain, o!());
+    }
+
+    let key = static_key(key);
+    Logger::root(
+        drain,
+        o!(key => FnValue(move |record| format_source_location(record, location))),
+    )
+}
+
+// The same format as the timestamps outputted by `slog_term::timestamp_{utc,local}`
+const TERM_TIMESTAMP_TEMPLATE: &str = "%b %d %H:%M:%S%.3f";
+
+/// Generates the timestamps of log records.
+#[derive(Debug, Clone)]
+pub struct Timestamper {
+    format: TimestampFormat,
+    timezone: TimeZone,
+}
+impl Timestamper {
+    pub fn new(format: TimestampFormat, timezone: TimeZone) -> Result<Self> {
+        if let TimestampFormat::Strftime(ref template) = format {
+            track_assert!(
+                StrftimeItems::new(template).all(|item| item != Item::Error),
+                ErrorKind::Invalid,
+                "Invalid strftime template: {:?}",
+                template
+            );
+        }
+        Ok(Timestamper { format, timezone })
+    }
+
+    /// Returns `true` if no timestamp is outputted.
+    pub fn is_disabled(&self) -> bool {
+        self.format == TimestampFormat::None
+    }
+
+    /// Returns the current timestamp, or `None` if no timestamp is outputted.
+    pub fn now(&self) -> Option<Timestamp> {
+        let now = Utc::now();
+        let text = match self.format {
+            TimestampFormat::None => return None,
+            TimestampFormat::UnixSecs => return Some(Timestamp::Number(now.timestamp())),
+            TimestampFormat::UnixMillis => return Some(Timestamp::Number(now.timestamp_millis())),
+            TimestampFormat::UnixNanos => {
+                let nanos =
+                    now.timestamp() * 1_000_000_000 + i64::from(now.timestamp_subsec_nanos());
+                return Some(Timestamp::Number(nanos));
+            }
+            TimestampFormat::Default => to_rfc3339(now, self.timezone, SecondsFormat::AutoSi),
+            TimestampFormat::Rfc3339Secs => to_rfc3339(now, self.timezone, SecondsFormat::Secs),
+            TimestampFormat::Rfc3339Millis => to_rfc3339(now, self.timezone, SecondsFormat::Millis),
+            TimestampFormat::Rfc3339Micros => to_rfc3339(now, self.timezone, SecondsFormat::Micros),
+            TimestampFormat::Rfc3339Nanos => to_rfc3339(now, self.timezone, SecondsFormat::Nanos),
+            TimestampFormat::Strftime(ref template) => {
+                format_timestamp(template, self.timezone, now)
+            }
+        };
+        Some(Timestamp::Text(text))
+    }
+
+    /// Writes the current timestamp (used as the timestamp function of `slog_term`).
+    pub fn write(&self, writer: &mut dyn io::Write) -> io::Result<()> {
+        match (&self.format, self.timezone) {
+            (&TimestampFormat::Default, TimeZone::Utc) => slog_term::timestamp_utc(writer),
+            (&TimestampFormat::Default, TimeZone::Local) => slog_term::timestamp_local(writer),
+            (&TimestampFormat::Default, timezone) => {
+                let now = format_timestamp(TERM_TIMESTAMP_TEMPLATE, timezone, Utc::now());
+                write!(writer, "{}", now)
+            }
+            _ => match self.now() {
+                Some(timestamp) => write!(writer, "{}", timestamp),
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+/// A timestamp generated by `Timestamper`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Timestamp {
+    Text(String),
+    Number(i64),
+}
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Timestamp::Text(ref t) => t.fmt(f),
+            Timestamp::Number(n) => n.fmt(f),
+        }
+    }
+}
+
+fn to_rfc3339(date_time: DateTime<Utc>, timezone: TimeZone, secs: SecondsFormat) -> String {
+    match timezone {
+        TimeZone::Utc => date_time.to_rfc3339_opts(secs, false),
+        TimeZone::Local => date_time.with_timezone(&Local).to_rfc3339_opts(secs, false),
+        TimeZone::Offset(offset) => date_time
+            .with_timezone(&offset)
+            .to_rfc3339_opts(secs, false),
+        TimeZone::Named(tz) => date_time.with_timezone(&tz).to_rfc3339_opts(secs, false),
+    }
+}
+
+pub fn format_timestamp(template: &str, timezone: TimeZone, date_time: DateTime<Utc>) -> String {
     match timezone {
-        TimeZone::Utc => slog_term::timestamp_utc,
-        TimeZone::Local => slog_term::timestamp_local,
+        TimeZone::Local => date_time.with_timezone(&Local).format(template).to_string(),
+        TimeZone::Utc => date_time.format(template).to_string(),
+        TimeZone::Offset(offset) => date_time
+            .with_timezone(&offset)
+            .format(template)
+            .to_string(),
+        TimeZone::Named(tz) => date_time.with_timezone(&tz).format(template).to_string(),
+    }
+}
+
+pub fn json_drain<W: io::Write>(writer: W, timestamper: Timestamper) -> Json<W> {
+    let mut builder = Json::new(writer).set_flush(true);
+    if !timestamper.is_disabled() {
+        builder = builder.add_key_value(o!(
+            "ts" => PushFnValue(move |_: &Record, ser| match timestamper.now() {
+                Some(Timestamp::Text(t)) => ser.emit(t),
+                Some(Timestamp::Number(n)) => ser.emit(n),
+                None => ser.emit(()),
+            }),
+        ));
+    }
+    builder
+        .add_key_value(o!(
+            "level" => FnValue(|record: &Record| record.level().as_str().to_lowercase()),
+            "msg" => PushFnValue(|record: &Record, ser| ser.emit(record.msg())),
+        ))
+        .build()
+}
+
+/// A drain which outputs log records in logfmt format.
+///
+/// Each record is outputted as a single line like
+/// `ts=2018-09-18T10:19:51.093+09:00 level=info msg="Hello World!" module=foo:12 key=value`.
+pub struct LogfmtDrain<W: io::Write> {
+    writer: RefCell<W>,
+    timestamper: Timestamper,
+}
+impl<W: io::Write> LogfmtDrain<W> {
+    pub fn new(writer: W, timestamper: Timestamper) -> Self {
+        LogfmtDrain {
+            writer: RefCell::new(writer),
+            timestamper,
+        }
+    }
+}
+impl<W: io::Write> Drain for LogfmtDrain<W> {
+    type Ok = ();
+    type Err = io::Error;
+    fn log(&self, record: &Record, logger_values: &OwnedKVList) -> io::Result<()> {
+        let mut line = String::new();
+        if let Some(timestamp) = self.timestamper.now() {
+            push_logfmt_pair(&mut line, "ts", &timestamp.to_string());
+        }
+        push_logfmt_pair(&mut line, "level", &record.level().as_str().to_lowercase());
+        push_logfmt_pair(&mut line, "msg", &record.msg().to_string());
+        {
+            let mut serializer = LogfmtSerializer(&mut line);
+            logger_values.serialize(record, &mut serializer)?;
+            record.kv().serialize(record, &mut serializer)?;
+        }
+        line.push('\n');
+
+        let mut writer = self.writer.borrow_mut();
+        writer.write_all(line.as_bytes())?;
+        writer.flush()
+    }
+}
+
+struct LogfmtSerializer<'a>(&'a mut String);
+impl<'a> slog::Serializer for LogfmtSerializer<'a> {
+    fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> slog::Result {
+        push_logfmt_pair(self.0, key, &val.to_string());
+        Ok(())
+    }
+}
+
+fn push_logfmt_pair(line: &mut String, key: &str, value: &str) {
+    if !line.is_empty() {
+        line.push(' ');
+    }
+
+    // Characters which cannot appear in keys are replaced with underscores
+    if key.is_empty() {
+        line.push('_');
+    }
+    for c in key.chars() {
+        if c <= ' ' || c == '=' || c == '"' || c.is_control() {
+            line.push('_');
+        } else {
+            line.push(c);
+        }
+    }
+    line.push('=');
+
+    let needs_quote = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c <= ' ' || c == '=' || c == '"' || c == '\\' || c.is_control());
+    if !needs_quote {
+        line.push_str(value);
+        return;
+    }
+    line.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            c if c.is_control() => line.push_str(&format!("\\u{{{:04x}}}", c as u32)),
+            c => line.push(c),
+        }
+    }
+    line.push('"');
+}
+
+// `slog` requires static keys, so dynamic keys are interned (and leaked) once per distinct string
+fn static_key(key: &str) -> &'static str {
+    static KEYS: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+    let mut keys = KEYS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(key) = keys.get(key) {
+        return key;
+    }
+    let key: &'static str = Box::leak(key.to_owned().into_boxed_str());
+    keys.insert(key);
+    key
+}
+
+/// Adds the static key/value pairs `fields` to the log records outputted via `logger`.
+pub fn with_fields(logger: Logger, fields: &[(String, FieldValue)]) -> Logger {
+    if fields.is_empty() {
+        return logger;
+    }
+    let fields = fields
+        .iter()
+        .map(|(key, value)| (static_key(key), value.clone()))
+        .collect();
+    logger.new(OwnedKV(StaticFields(fields)))
+}
+
+struct StaticFields(Vec<(&'static str, FieldValue)>);
+impl KV for StaticFields {
+    fn serialize(&self, _record: &Record, serializer: &mut dyn slog::Serializer) -> slog::Result {
+        for &(key, ref value) in &self.0 {
+            match *value {
+                FieldValue::Bool(v) => serializer.emit_bool(key, v)?,
+                FieldValue::Integer(v) => serializer.emit_i64(key, v)?,
+                FieldValue::Float(v) => serializer.emit_f64(key, v)?,
+                FieldValue::String(ref v) => serializer.emit_str(key, v)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The key under which `ThreadDrain` stores the ID of the logging thread.
+pub const THREAD_ID_KEY: &str = "thread_id";
+
+/// Adds the fields specified by `fields` to the log records outputted via `logger`.
+pub fn with_metadata(logger: Logger, fields: &MetadataFields) -> Logger {
+    let mut metadata = StaticMetadata::default();
+    if fields.pid {
+        metadata.pid = Some(process::id());
+    }
+    if fields.hostname {
+        metadata.add("hostname", hostname());
+    }
+    if fields.executable {
+        metadata.add("executable", executable_name());
+    }
+    metadata.add("app_name", fields.app_name.clone());
+    metadata.add("app_version", fields.app_version.clone());
+
+    let logger = if metadata.pid.is_none() && metadata.values.is_empty() {
+        logger
+    } else {
+        logger.new(OwnedKV(metadata))
+    };
+    if fields.thread_name || fields.thread_id {
+        let drain = ThreadDrain::new(logger, fields.thread_name, fields.thread_id);
+        Logger::root(drain, o!())
+    } else {
+        logger
+    }
+}
+
+#[derive(Default)]
+struct StaticMetadata {
+    pid: Option<u32>,
+    values: Vec<(&'static str, String)>,
+}
+impl StaticMetadata {
+    fn add(&mut self, key: &'static str, value: Option<String>) {
+        if let Some(value) = value {
+            self.values.push((key, value));
+        }
+    }
+}
+impl KV for StaticMetadata {
+    fn serialize(&self, _record: &Record, serializer: &mut dyn slog::Serializer) -> slog::Result {
+        if let Some(pid) = self.pid {
+            serializer.emit_u32("pid", pid)?;
+        }
+        for &(key, ref value) in &self.values {
+            serializer.emit_str(key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A drain which adds the name and/or the ID of the current thread to log records
+/// as the `thread` and `thread_id` keys respectively.
+///
+/// This must be placed in front of asynchronous drains,
+/// otherwise the name of the worker thread would be added instead of the logging thread.
+pub struct ThreadDrain<D> {
+    drain: D,
+    name: bool,
+    id: bool,
+}
+impl<D: Drain> ThreadDrain<D> {
+    pub fn new(drain: D, name: bool, id: bool) -> Self {
+        ThreadDrain { drain, name, id }
+    }
+}
+impl<D: Drain<Ok = ()>> Drain for ThreadDrain<D> {
+    type Ok = ();
+    type Err = D::Err;
+    fn log(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> ::std::result::Result<Self::Ok, Self::Err> {
+        if !self.drain.is_enabled(record.level()) {
+            return Ok(());
+        }
+        let result = CURRENT_THREAD.try_with(|thread| {
+            let fields = ThreadFields {
+                name: if self.name { Some(&thread.name) } else { None },
+                id: if self.id { Some(thread.id) } else { None },
+            };
+            self.log_with_fields(record, logger_values, fields)
+        });
+        match result {
+            Ok(result) => result,
+            Err(_) => {
+                // The thread local storage has been destroyed
+                let fields = ThreadFields {
+                    name: None,
+                    id: None,
+                };
+                self.log_with_fields(record, logger_values, fields)
+            }
+        }
+    }
+    fn is_enabled(&self, level: Level) -> bool {
+        self.drain.is_enabled(level)
+    }
+}
+impl<D: Drain> ThreadDrain<D> {
+    fn log_with_fields(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+        fields: ThreadFields,
+    ) -> ::std::result::Result<D::Ok, D::Err> {
+        let location = RecordLocation {
+            file: record.file(),
+            line: record.line(),
+            column: record.column(),
+            function: record.function(),
+            module: record.module(),
+        };
+        let record_static = RecordStatic {
+            location: &location,
+            level: record.level(),
+            tag: record.tag(),
+        };
+        let kv = (fields, record.kv());
+        self.drain.log(
+            &Record::new(&record_static, record.msg(), BorrowedKV(&kv)),
+            logger_values,
+        )
+    }
+}
+
+static NEXT_THREAD_ID: AtomicUsize = AtomicUsize::new(1);
+
+struct CurrentThread {
+    id: u64,
+    name: String,
+}
+
+thread_local! {
+    // Assigned at the first record of each thread
+    static CURRENT_THREAD: CurrentThread = {
+        let id = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed) as u64;
+        let name = match thread::current().name() {
+            Some(name) => name.to_owned(),
+            None => format!("thread-{}", id),
+        };
+        CurrentThread { id, name }
+    };
+}
+
+struct ThreadFields<'a> {
+    name: Option<&'a str>,
+    id: Option<u64>,
+}
+impl<'a> KV for ThreadFields<'a> {
+    fn serialize(&self, _record: &Record, serializer: &mut dyn slog::Serializer) -> slog::Result {
+        if let Some(name) = self.name {
+            serializer.emit_str(THREAD_KEY, name)?;
+        }
+        if let Some(id) = self.id {
+            serializer.emit_u64(THREAD_ID_KEY, id)?;
+        }
+        Ok(())
+    }
+}
+
+/// A serializer which collects key/value pairs as strings.
+#[derive(Debug, Default)]
+pub struct KVCollector {
+    pub pairs: Vec<(String, String)>,
+}
+impl KVCollector {
+    pub fn collect(
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> slog::Result<Vec<(String, String)>> {
+        let mut collector = KVCollector::default();
+        record.kv().serialize(record, &mut collector)?;
+        logger_values.serialize(record, &mut collector)?;
+        Ok(collector.pairs)
+    }
+}
+impl slog::Serializer for KVCollector {
+    fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> slog::Result {
+        self.pairs.push((key.to_string(), val.to_string()));
+        Ok(())
+    }
+}
+
+pub fn hostname() -> Option<String> {
+    hostname::get_hostname()
+}
+
+pub fn executable_name() -> Option<String> {
+    env::current_exe().ok().and_then(|path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    })
+}
+
+/// An asynchronous drain which counts the log records dropped due to channel overflows.
+pub struct AsyncDrain {
+    // `None` only while being dropped
+    core: Option<AsyncCore>,
+
+    // The drain processing records in the worker thread, which is also used for the final report
+    inner: Arc<Mutex<dyn Drain<Ok = (), Err = Never> + Send>>,
+    overflow_strategy: OverflowStrategy,
+    dropped: DroppedRecordCounter,
+    unreported: AtomicUsize,
+    finished: Arc<AtomicBool>,
+    last_report: Mutex<Option<Instant>>,
+}
+impl AsyncDrain {
+    /// If `guard` is `Some`, an `AsyncGuard` which joins the worker thread is added to it.
+    pub fn new<D>(
+        drain: D,
+        channel_size: usize,
+        overflow_strategy: OverflowStrategy,
+        dropped: DroppedRecordCounter,
+        guard: Option<&mut LoggerGuard>,
+    ) -> Self
+    where
+        D: Drain<Ok = (), Err = Never> + Send + 'static,
+    {
+        let inner = Arc::new(Mutex::new(drain));
+        let builder = AsyncCore::custom(SharedDrain(inner.clone()))
+            .chan_size(channel_size)
+            .blocking(overflow_strategy == OverflowStrategy::Block);
+        let finished = Arc::new(AtomicBool::new(false));
+        let core = if let Some(guard) = guard {
+            let (core, async_guard) = builder.build_with_guard();
+            guard.add(AsyncDrainGuard {
+                finished: finished.clone(),
+                _guard: async_guard,
+            });
+            core
+        } else {
+            builder.build()
+        };
+        AsyncDrain {
+            core: Some(core),
+            inner,
+            overflow_strategy,
+            dropped,
+            unreported: AtomicUsize::new(0),
+            finished,
+            last_report: Mutex::new(None),
+        }
+    }
+
+    // Returns `false` if there are dropped records which have not been reported yet
+    fn report_dropped(&self, logger_values: &OwnedKVList, force: bool) -> bool {
+        if self.overflow_strategy != OverflowStrategy::DropAndReport
+            || self.unreported.load(Ordering::Relaxed) == 0
+            || self.finished.load(Ordering::Relaxed)
+        {
+            return true;
+        }
+
+        let mut last_report = match self.last_report.try_lock() {
+            Ok(last_report) => last_report,
+            Err(_) => return false,
+        };
+        let interval = Duration::from_secs(1);
+        if !force && last_report.map_or(false, |t| t.elapsed() < interval) {
+            return false;
+        }
+
+        let count = self.unreported.swap(0, Ordering::Relaxed);
+        let result = match self.core {
+            Some(ref core) => log_dropped_report(core, count, logger_values),
+            None => Ok(()),
+        };
+        if result.is_err() {
+            self.unreported.fetch_add(count, Ordering::Relaxed);
+            false
+        } else {
+            *last_report = Some(Instant::now());
+            true
+        }
+    }
+}
+impl Drain for AsyncDrain {
+    type Ok = ();
+    type Err = AsyncError;
+    fn log(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> ::std::result::Result<(), AsyncError> {
+        self.report_dropped(logger_values, false);
+        let core = self.core.as_ref().expect("Never fails");
+        match core.log(record, logger_values) {
+            Err(_) if self.finished.load(Ordering::Relaxed) => {
+                // The worker thread has been joined by `LoggerGuard`
+                self.dropped.increment();
+                Ok(())
+            }
+            Err(AsyncError::Full) => {
+                self.dropped.increment();
+                self.unreported.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+impl Drop for AsyncDrain {
+    fn drop(&mut self) {
+        let count = self.unreported.swap(0, Ordering::Relaxed);
+
+        // Joins the worker thread (unless `LoggerGuard` does it),
+        // so that the report follows the records in the channel
+        drop(self.core.take());
+        if self.overflow_strategy == OverflowStrategy::DropAndReport && count > 0 {
+            let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            let _ = log_dropped_report(&*inner, count, &o!().into());
+        }
+    }
+}
+
+fn log_dropped_report<D: Drain + ?Sized>(
+    drain: &D,
+    count: usize,
+    logger_values: &OwnedKVList,
+) -> ::std::result::Result<D::Ok, D::Err> {
+    drain.log(
+        &record!(
+            Level::Warning,
+            "",
+            &format_args!("{} log records were dropped due to channel overflow", count),
+            b!("dropped_records" => count)
+        ),
+        logger_values,
+    )
+}
+
+struct SharedDrain<D>(Arc<Mutex<D>>);
+impl<D> Drain for SharedDrain<D>
+where
+    D: Drain<Ok = (), Err = Never>,
+{
+    type Ok = ();
+    type Err = Never;
+    fn log(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> ::std::result::Result<(), Never> {
+        let drain = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        drain.log(record, logger_values)
+    }
+    fn is_enabled(&self, level: Level) -> bool {
+        let drain = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        drain.is_enabled(level)
+    }
+}
+
+struct AsyncDrainGuard {
+    finished: Arc<AtomicBool>,
+    _guard: AsyncGuard,
+}
+impl Drop for AsyncDrainGuard {
+    fn drop(&mut self) {
+        // `_guard` joins the worker thread after this
+        self.finished.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A drain which processes log records asynchronously or synchronously.
+#[allow(clippy::large_enum_variant)]
+pub enum AsyncOrSyncDrain<D: Drain<Ok = (), Err = Never>> {
+    Async(Fuse<AsyncDrain>),
+    Sync(Fuse<Mutex<D>>),
+}
+impl<D> Drain for AsyncOrSyncDrain<D>
+where
+    D: Drain<Ok = (), Err = Never>,
+{
+    type Ok = ();
+    type Err = Never;
+    fn log(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> ::std::result::Result<(), Never> {
+        match *self {
+            AsyncOrSyncDrain::Async(ref d) => d.log(record, logger_values),
+            AsyncOrSyncDrain::Sync(ref d) => d.log(record, logger_values),
+        }
+    }
+    fn is_enabled(&self, level: Level) -> bool {
+        match *self {
+            AsyncOrSyncDrain::Async(ref d) => d.is_enabled(level),
+            AsyncOrSyncDrain::Sync(ref d) => d.is_enabled(level),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "tracing"))]
+    use std::fs;
+    #[cfg(not(feature = "tracing"))]
+    use tempdir::TempDir;
+
+    use super::*;
+    #[cfg(not(feature = "tracing"))]
+    use file::FileLoggerConfig;
+    #[cfg(not(feature = "tracing"))]
+    use test_util::lock_global_state;
+
+    #[test]
+    fn static_key_is_interned() {
+        let (x, y) = (String::from("foo"), String::from("foo"));
+        let a = static_key(&x);
+        let b = static_key(&y);
+        assert_eq!(a, "foo");
+        assert!(::std::ptr::eq(a, b));
+    }
+
+    type Records = Arc<Mutex<Vec<Vec<(String, String)>>>>;
+
+    struct CollectingDrain(Records);
+    impl Drain for CollectingDrain {
+        type Ok = ();
+        type Err = Never;
+        fn log(
+            &self,
+            record: &Record,
+            logger_values: &OwnedKVList,
+        ) -> ::std::result::Result<(), Never> {
+            let pairs = KVCollector::collect(record, logger_values).expect("Never fails");
+            self.0.lock().unwrap().push(pairs);
+            Ok(())
+        }
+        fn is_enabled(&self, level: Level) -> bool {
+            level.is_at_least(Level::Info)
+        }
+    }
+
+    #[test]
+    fn thread_drain_works() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let drain = ThreadDrain::new(CollectingDrain(records.clone()), true, true);
+        let logger = Logger::root(drain, o!());
+
+        info!(logger, "foo");
+        debug!(logger, "filtered out");
+        let logger = logger.clone();
+        thread::spawn(move || info!(logger, "bar"))
+            .join()
+            .expect("Cannot join the logging thread");
+
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        let value = |i: usize, key: &str| {
+            records[i]
+                .iter()
+                .find(|&(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                .expect("No such key")
+        };
+        let name = thread::current().name().map(String::from);
+        assert_eq!(
+            Some(value(0, THREAD_KEY)),
+            name.or_else(|| Some(format!("thread-{}", value(0, THREAD_ID_KEY))))
+        );
+        assert_ne!(value(0, THREAD_ID_KEY), value(1, THREAD_ID_KEY));
+        assert_eq!(
+            value(1, THREAD_KEY),
+            format!("thread-{}", value(1, THREAD_ID_KEY))
+        );
+    }
+
+    // `init` can be called only once in a process, so this is covered by
+    // `tracing_bridge::tests::init_keeps_existing_global_subscriber` if the feature is enabled
+    #[cfg(not(feature = "tracing"))]
+    #[test]
+    fn init_works() -> Result<()> {
+        let _lock = lock_global_state();
+        let dir = TempDir::new("sloggers_test")?;
+        let config = LoggerConfig::File(FileLoggerConfig {
+            path: dir.path().join("foo.log"),
+            ..FileLoggerConfig::default()
+        });
+
+        let guard = init(&config)?;
+        assert_eq!(log::max_level(), log::LevelFilter::Info);
+        info!(slog_scope::logger(), "hello");
+        drop(guard);
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.contains("hello"));
+
+        Ok(())
     }
 }
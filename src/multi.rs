@@ -0,0 +1,236 @@
+//! Multi-destination logger.
+use slog::{Drain, Level, Logger, Never, OwnedKVList, Record};
+
+use types::LevelHandle;
+use {Build, Config, ErrorKind, LoggerBuilder, LoggerConfig, LoggerGuard, Result};
+
+/// A logger builder which build loggers that duplicate log records to every child logger.
+///
+/// Each child logger keeps its own settings (e.g., level, format and KV filter).
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate slog;
+/// extern crate sloggers;
+///
+/// use sloggers::{Build, LoggerBuilder};
+/// use sloggers::multi::MultiLoggerBuilder;
+/// use sloggers::null::NullLoggerBuilder;
+/// use sloggers::terminal::TerminalLoggerBuilder;
+///
+/// # fn main() {
+/// let mut builder = MultiLoggerBuilder::new();
+/// builder.add(LoggerBuilder::Terminal(TerminalLoggerBuilder::new()));
+/// builder.add(LoggerBuilder::Null(NullLoggerBuilder));
+///
+/// let logger = builder.build().unwrap();
+/// info!(logger, "Hello World!");
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct MultiLoggerBuilder {
+    builders: Vec<LoggerBuilder>,
+}
+impl MultiLoggerBuilder {
+    /// Makes a new `MultiLoggerBuilder` instance which has no child loggers.
+    pub fn new() -> Self {
+        MultiLoggerBuilder {
+            builders: Vec::new(),
+        }
+    }
+
+    /// Adds a child logger builder.
+    pub fn add(&mut self, builder: LoggerBuilder) -> &mut Self {
+        self.builders.push(builder);
+        self
+    }
+
+    /// Builds a logger and a handle for changing the log level of the logger at runtime.
+    ///
+    /// Setting a severity via the handle changes the levels of all the child loggers.
+    pub fn build_with_level_handle(&self) -> Result<(Logger, LevelHandle)> {
+        track!(self.build_logger(None))
+    }
+
+    pub(crate) fn build_logger(
+        &self,
+        mut guard: Option<&mut LoggerGuard>,
+    ) -> Result<(Logger, LevelHandle)> {
+        let mut loggers = Vec::with_capacity(self.builders.len());
+        let mut handles = Vec::with_capacity(self.builders.len());
+        for builder in &self.builders {
+            let child_guard = guard.as_deref_mut();
+            let (logger, handle) = track!(builder.build_logger(child_guard))?;
+            loggers.push(logger);
+            handles.push(handle);
+        }
+        let logger = Logger::root(MultiDrain(loggers), o!());
+        Ok((logger, LevelHandle::merge(handles)))
+    }
+}
+impl Build for MultiLoggerBuilder {
+    fn build(&self) -> Result<Logger> {
+        let (logger, _) = track!(self.build_logger(None))?;
+        Ok(logger)
+    }
+
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let mut guard = LoggerGuard::new();
+        let (logger, _) = track!(self.build_logger(Some(&mut guard)))?;
+        Ok((logger, guard))
+    }
+}
+
+struct MultiDrain(Vec<Logger>);
+impl Drain for MultiDrain {
+    type Ok = ();
+    type Err = Never;
+    fn log(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> ::std::result::Result<(), Never> {
+        for logger in &self.0 {
+            Drain::log(logger, record, logger_values)?;
+        }
+        Ok(())
+    }
+    fn is_enabled(&self, level: Level) -> bool {
+        self.0.iter().any(|logger| logger.is_enabled(level))
+    }
+}
+
+/// The configuration of `MultiLoggerBuilder`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct MultiLoggerConfig {
+    /// Child loggers.
+    ///
+    /// At least one child logger must be specified.
+    #[serde(default)]
+    pub loggers: Vec<LoggerConfig>,
+}
+impl Config for MultiLoggerConfig {
+    type Builder = MultiLoggerBuilder;
+    fn try_to_builder(&self) -> Result<Self::Builder> {
+        track_assert!(
+            !self.loggers.is_empty(),
+            ErrorKind::Invalid,
+            "No child loggers are specified"
+        );
+
+        let mut builder = MultiLoggerBuilder::new();
+        for config in &self.loggers {
+            builder.add(track!(config.try_to_builder())?);
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serdeconv;
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+    use std::thread;
+    use std::time::Duration;
+    use tempdir::TempDir;
+    use trackable::error::ErrorKindExt;
+
+    use super::*;
+    use file::FileLoggerBuilder;
+    use test_util::lock_global_state;
+    use types::{Format, Severity};
+    use {Build, LoggerBuilder, LoggerConfig, Result};
+
+    #[test]
+    fn multi_logger_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let mut info = FileLoggerBuilder::new(dir.path().join("info.log"));
+        info.level(Severity::Info);
+        let mut debug = FileLoggerBuilder::new(dir.path().join("debug.log"));
+        debug.level(Severity::Debug);
+        let logger = MultiLoggerBuilder::new()
+            .add(LoggerBuilder::File(info))
+            .add(LoggerBuilder::File(debug))
+            .build()?;
+
+        debug!(logger, "foo");
+        info!(logger, "bar");
+        thread::sleep(Duration::from_millis(50));
+
+        let info = fs::read_to_string(dir.path().join("info.log"))?;
+        assert!(!info.contains("foo"));
+        assert!(info.contains("bar"));
+
+        let debug = fs::read_to_string(dir.path().join("debug.log"))?;
+        assert!(debug.contains("foo"));
+        assert!(debug.contains("bar"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn multi_config_without_loggers_is_rejected() {
+        let config = MultiLoggerConfig::default();
+        let e = config.try_to_builder().err().map(|e| e.kind().clone());
+        assert_eq!(e, Some(ErrorKind::Invalid));
+    }
+
+    #[test]
+    fn multi_config_env_override_works() -> Result<()> {
+        let _lock = lock_global_state();
+        let mut config: LoggerConfig = track!(serdeconv::from_toml_str(
+            r#"
+type = "multi"
+
+[[loggers]]
+type = "file"
+path = "foo.log"
+format = "compact"
+
+[[loggers]]
+type = "syslog"
+
+[[loggers]]
+type = "terminal"
+"#
+        )
+        .map_err(|e| ErrorKind::Invalid.cause(e)))?;
+
+        // `FORMAT` and `PATH` are not applied to the children
+        env::set_var("SLOGGERS_MULTI_TEST_LEVEL", "debug");
+        env::set_var("SLOGGERS_MULTI_TEST_FORMAT", "json");
+        env::set_var("SLOGGERS_MULTI_TEST_PATH", "bar.log");
+        let result = config.apply_env("SLOGGERS_MULTI_TEST");
+        env::remove_var("SLOGGERS_MULTI_TEST_LEVEL");
+        env::remove_var("SLOGGERS_MULTI_TEST_FORMAT");
+        env::remove_var("SLOGGERS_MULTI_TEST_PATH");
+        track!(result)?;
+
+        let loggers = match config {
+            LoggerConfig::Multi(c) => c.loggers,
+            _ => unreachable!(),
+        };
+        match loggers[0] {
+            LoggerConfig::File(ref c) => {
+                assert_eq!(c.level, Severity::Debug);
+                assert_eq!(c.format, Format::Compact);
+                assert_eq!(c.path, Path::new("foo.log"));
+            }
+            _ => unreachable!(),
+        }
+        match loggers[1] {
+            LoggerConfig::Syslog(ref c) => assert_eq!(c.level, Severity::Debug),
+            _ => unreachable!(),
+        }
+        match loggers[2] {
+            LoggerConfig::Terminal(ref c) => assert_eq!(c.level, Severity::Debug),
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,317 @@
+use slog::{BorrowedKV, Drain, Level, OwnedKVList, Record, RecordLocation, RecordStatic, SingleKV};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use build::LoggerGuard;
+use types::RateLimit;
+
+/// The key under which the number of suppressed records is stored in summary records.
+pub const SUPPRESSED_KEY: &str = "suppressed";
+
+type BucketKey = (Template, Level, &'static str);
+
+// The template of a message.
+//
+// Only the messages without arguments have templates available at runtime,
+// so the location of the logging statement is used for the other messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Template {
+    Static(&'static str),
+    Location(&'static str, u32),
+}
+impl Template {
+    fn of(record: &Record) -> Self {
+        match record.msg().as_str() {
+            Some(s) => Template::Static(s),
+            None => Template::Location(record.file(), record.line()),
+        }
+    }
+}
+
+/// A drain which drops log records exceeding a `RateLimit`.
+///
+/// If no limit is specified, records are passed through as they are.
+///
+/// The summary of the records suppressed in a window is outputted by a background thread
+/// when the window closes (i.e., the bucket is refilled), or when this drain is dropped.
+pub struct RateLimitDrain<D: Drain> {
+    limit: Option<RateLimit>,
+    shared: Arc<Shared<D>>,
+}
+impl<D> RateLimitDrain<D>
+where
+    D: Drain + Send + Sync + 'static,
+{
+    /// If both `limit` and `guard` are `Some`, a guard which outputs the pending summaries is added to `guard`.
+    pub fn new(drain: D, limit: Option<RateLimit>, guard: Option<&mut LoggerGuard>) -> Self {
+        let shared = Arc::new(Shared {
+            drain,
+            state: Mutex::new(State {
+                buckets: HashMap::new(),
+                deadlines: BinaryHeap::new(),
+                stopped: false,
+            }),
+            wakeup: Condvar::new(),
+            ticker: Mutex::new(None),
+        });
+        if let Some(limit) = limit {
+            let ticker_shared = shared.clone();
+            let ticker = thread::spawn(move || ticker_shared.run_ticker(limit));
+            *shared.ticker.lock().unwrap_or_else(|e| e.into_inner()) = Some(ticker);
+            if let Some(guard) = guard {
+                guard.add(RateLimitGuard(shared.clone()));
+            }
+        }
+        RateLimitDrain { limit, shared }
+    }
+}
+impl<D: Drain> Drain for RateLimitDrain<D> {
+    type Ok = Option<D::Ok>;
+    type Err = D::Err;
+    fn log(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> ::std::result::Result<Self::Ok, Self::Err> {
+        let limit = match self.limit {
+            None => return self.shared.drain.log(record, logger_values).map(Some),
+            Some(limit) => limit,
+        };
+
+        let (pass, summary) = self.acquire(limit, record, logger_values);
+        if let Some(summary) = summary {
+            self.shared.log_summary(&summary)?;
+        }
+        if pass {
+            self.shared.drain.log(record, logger_values).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+    fn is_enabled(&self, level: Level) -> bool {
+        self.shared.drain.is_enabled(level)
+    }
+}
+impl<D: Drain> RateLimitDrain<D> {
+    // Returns whether `record` should pass, and the summary of the window closed before the record
+    fn acquire(
+        &self,
+        limit: RateLimit,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> (bool, Option<Summary>) {
+        let key = (Template::of(record), record.level(), record.module());
+        let now = Instant::now();
+        let mut state = self.shared.lock_state();
+        let state = &mut *state;
+        let bucket = state.buckets.entry(key).or_insert_with(|| Bucket {
+            tokens: f64::from(limit.burst),
+            last_refill: now,
+            suppressed: None,
+        });
+        bucket.refill(limit, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+
+            // The background thread has not outputted the summary yet
+            return (true, bucket.suppressed.take());
+        }
+
+        match bucket.suppressed {
+            Some(ref mut s) => {
+                s.count += 1;
+                s.logger_values = logger_values.clone();
+            }
+            None => {
+                let deadline = bucket.refill_deadline(limit, now);
+                bucket.suppressed = Some(Summary {
+                    location: RecordLocation {
+                        file: record.file(),
+                        line: record.line(),
+                        column: record.column(),
+                        function: record.function(),
+                        module: record.module(),
+                    },
+                    level: record.level(),
+                    tag: record.tag().to_owned(),
+                    logger_values: logger_values.clone(),
+                    count: 1,
+                    deadline,
+                });
+                if let Some(deadline) = deadline {
+                    state.deadlines.push(Reverse((deadline, key)));
+                    self.shared.wakeup.notify_one();
+                }
+            }
+        }
+        (false, None)
+    }
+}
+impl<D: Drain> Drop for RateLimitDrain<D> {
+    fn drop(&mut self) {
+        self.shared.shutdown();
+    }
+}
+
+// A guard which outputs the pending summaries before the succeeding guards (e.g., `AsyncGuard`) are dropped
+struct RateLimitGuard<D: Drain>(Arc<Shared<D>>);
+impl<D: Drain> Drop for RateLimitGuard<D> {
+    fn drop(&mut self) {
+        self.0.shutdown();
+    }
+}
+
+struct Shared<D: Drain> {
+    drain: D,
+    state: Mutex<State>,
+    wakeup: Condvar,
+    ticker: Mutex<Option<JoinHandle<()>>>,
+}
+impl<D: Drain> Shared<D> {
+    fn lock_state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn run_ticker(&self, limit: RateLimit) {
+        let mut state = self.lock_state();
+        while !state.stopped {
+            let now = Instant::now();
+            let deadline = match state.deadlines.peek() {
+                None => {
+                    state = self.wakeup.wait(state).unwrap_or_else(|e| e.into_inner());
+                    continue;
+                }
+                Some(&Reverse((deadline, _))) => deadline,
+            };
+            if now < deadline {
+                state = self
+                    .wakeup
+                    .wait_timeout(state, deadline - now)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0;
+                continue;
+            }
+
+            let summary = {
+                let state = &mut *state;
+                let key = match state.deadlines.pop() {
+                    Some(Reverse((_, key))) => key,
+                    None => continue,
+                };
+                let bucket = match state.buckets.get_mut(&key) {
+                    Some(bucket) => bucket,
+                    None => continue,
+                };
+                match bucket.suppressed {
+                    Some(ref s) if s.deadline == Some(deadline) => {}
+                    _ => {
+                        // The summary has already been outputted by a succeeding record
+                        continue;
+                    }
+                }
+                bucket.refill(limit, now);
+                if bucket.tokens >= 1.0 {
+                    bucket.suppressed.take()
+                } else {
+                    let deadline = bucket.refill_deadline(limit, now);
+                    if let Some(deadline) = deadline {
+                        state.deadlines.push(Reverse((deadline, key)));
+                    }
+                    if let Some(ref mut s) = bucket.suppressed {
+                        s.deadline = deadline;
+                    }
+                    None
+                }
+            };
+            if let Some(summary) = summary {
+                drop(state);
+                let _ = self.log_summary(&summary);
+                state = self.lock_state();
+            }
+        }
+    }
+
+    // Stops the background thread, and outputs the summaries of the windows which are still open
+    fn shutdown(&self) {
+        self.lock_state().stopped = true;
+        self.wakeup.notify_all();
+        let ticker = self.ticker.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(ticker) = ticker {
+            let _ = ticker.join();
+        }
+
+        let summaries = {
+            let mut state = self.lock_state();
+            state.deadlines.clear();
+            state
+                .buckets
+                .values_mut()
+                .filter_map(|b| b.suppressed.take())
+                .collect::<Vec<_>>()
+        };
+        for summary in &summaries {
+            let _ = self.log_summary(summary);
+        }
+    }
+
+    fn log_summary(&self, summary: &Summary) -> ::std::result::Result<D::Ok, D::Err> {
+        let record_static = RecordStatic {
+            location: &summary.location,
+            level: summary.level,
+            tag: &summary.tag,
+        };
+        let kv = SingleKV(SUPPRESSED_KEY, summary.count);
+        self.drain.log(
+            &Record::new(
+                &record_static,
+                &format_args!("Suppressed {} similar messages", summary.count),
+                BorrowedKV(&kv),
+            ),
+            &summary.logger_values,
+        )
+    }
+}
+
+struct State {
+    buckets: HashMap<BucketKey, Bucket>,
+
+    // The times when the buckets which have suppressed records are refilled (the earliest first)
+    deadlines: BinaryHeap<Reverse<(Instant, BucketKey)>>,
+    stopped: bool,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    suppressed: Option<Summary>,
+}
+impl Bucket {
+    fn refill(&mut self, limit: RateLimit, now: Instant) {
+        let elapsed = now - self.last_refill;
+        let elapsed = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) * 1e-9;
+        self.tokens = (self.tokens + elapsed * limit.rate).min(f64::from(limit.burst));
+        self.last_refill = now;
+    }
+
+    // Returns `None` if the time is too far to be represented
+    fn refill_deadline(&self, limit: RateLimit, now: Instant) -> Option<Instant> {
+        let nanos = ((1.0 - self.tokens) / limit.rate * 1e9).ceil();
+        if nanos >= u64::MAX as f64 {
+            return None;
+        }
+        now.checked_add(Duration::from_nanos(nanos as u64))
+    }
+}
+
+// The records suppressed in a window, which are reported as a single record
+struct Summary {
+    location: RecordLocation,
+    level: Level,
+    tag: String,
+    logger_values: OwnedKVList,
+    count: u64,
+    deadline: Option<Instant>,
+}
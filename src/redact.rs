@@ -0,0 +1,232 @@
+use regex::Regex;
+use slog::{
+    self, BorrowedKV, Drain, Key, Level, OwnedKV, OwnedKVList, Record, RecordLocation,
+    RecordStatic, Serializer, KV,
+};
+use std::fmt;
+
+/// The string which replaces sensitive values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Rules for finding sensitive values in log records.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    keys: Vec<String>,
+    patterns: Vec<Regex>,
+}
+impl Redactor {
+    pub fn add_key(&mut self, key: &str) {
+        self.keys.push(key.to_owned());
+    }
+
+    pub fn add_pattern(&mut self, pattern: Regex) {
+        self.patterns.push(pattern);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.patterns.is_empty()
+    }
+
+    fn is_sensitive_key(&self, key: &str) -> bool {
+        self.keys.iter().any(|k| k.eq_ignore_ascii_case(key))
+    }
+
+    fn redact_str(&self, s: &str) -> String {
+        let mut s = s.to_owned();
+        for pattern in &self.patterns {
+            if pattern.is_match(&s) {
+                s = pattern.replace_all(&s, REDACTED).into_owned();
+            }
+        }
+        s
+    }
+}
+
+/// A drain which replaces sensitive values in log records with `"[REDACTED]"`.
+///
+/// - The values whose keys are registered in the `Redactor` are replaced entirely
+/// - The parts of the string values and the messages which match the patterns are replaced
+pub struct RedactDrain<D> {
+    drain: D,
+    redactor: Redactor,
+}
+impl<D: Drain> RedactDrain<D> {
+    pub fn new(drain: D, redactor: Redactor) -> Self {
+        RedactDrain { drain, redactor }
+    }
+}
+impl<D: Drain> Drain for RedactDrain<D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+    fn log(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> ::std::result::Result<Self::Ok, Self::Err> {
+        if self.redactor.is_empty() {
+            return self.drain.log(record, logger_values);
+        }
+
+        let mut record_values = RedactingSerializer::new(&self.redactor);
+        let _ = record.kv().serialize(record, &mut record_values);
+        let mut values = RedactingSerializer::new(&self.redactor);
+        let _ = logger_values.serialize(record, &mut values);
+        let values = OwnedKVList::from(OwnedKV(RedactedValues(values.values)));
+        let message = self.redactor.redact_str(&record.msg().to_string());
+
+        let location = RecordLocation {
+            file: record.file(),
+            line: record.line(),
+            column: record.column(),
+            function: record.function(),
+            module: record.module(),
+        };
+        let record_static = RecordStatic {
+            location: &location,
+            level: record.level(),
+            tag: record.tag(),
+        };
+        let record_values = RedactedValues(record_values.values);
+        self.drain.log(
+            &Record::new(
+                &record_static,
+                &format_args!("{}", message),
+                BorrowedKV(&record_values),
+            ),
+            &values,
+        )
+    }
+    fn is_enabled(&self, level: Level) -> bool {
+        self.drain.is_enabled(level)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Str(String),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+    Unit,
+    None,
+}
+
+struct RedactedValues(Vec<(Key, Value)>);
+impl KV for RedactedValues {
+    fn serialize(&self, _record: &Record, serializer: &mut dyn Serializer) -> slog::Result {
+        for &(key, ref value) in &self.0 {
+            match *value {
+                Value::Str(ref v) => serializer.emit_str(key, v)?,
+                Value::I64(v) => serializer.emit_i64(key, v)?,
+                Value::U64(v) => serializer.emit_u64(key, v)?,
+                Value::F64(v) => serializer.emit_f64(key, v)?,
+                Value::Bool(v) => serializer.emit_bool(key, v)?,
+                Value::Unit => serializer.emit_unit(key)?,
+                Value::None => serializer.emit_none(key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+struct RedactingSerializer<'a> {
+    redactor: &'a Redactor,
+    values: Vec<(Key, Value)>,
+}
+impl<'a> RedactingSerializer<'a> {
+    fn new(redactor: &'a Redactor) -> Self {
+        RedactingSerializer {
+            redactor,
+            values: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, key: Key, value: Value) -> slog::Result {
+        let value = if self.redactor.is_sensitive_key(key) {
+            Value::Str(REDACTED.to_owned())
+        } else if let Value::Str(s) = value {
+            Value::Str(self.redactor.redact_str(&s))
+        } else {
+            value
+        };
+        self.values.push((key, value));
+        Ok(())
+    }
+}
+impl<'a> Serializer for RedactingSerializer<'a> {
+    fn emit_usize(&mut self, key: Key, val: usize) -> slog::Result {
+        self.push(key, Value::U64(val as u64))
+    }
+    fn emit_isize(&mut self, key: Key, val: isize) -> slog::Result {
+        self.push(key, Value::I64(val as i64))
+    }
+    fn emit_bool(&mut self, key: Key, val: bool) -> slog::Result {
+        self.push(key, Value::Bool(val))
+    }
+    fn emit_char(&mut self, key: Key, val: char) -> slog::Result {
+        self.push(key, Value::Str(val.to_string()))
+    }
+    fn emit_u8(&mut self, key: Key, val: u8) -> slog::Result {
+        self.push(key, Value::U64(u64::from(val)))
+    }
+    fn emit_i8(&mut self, key: Key, val: i8) -> slog::Result {
+        self.push(key, Value::I64(i64::from(val)))
+    }
+    fn emit_u16(&mut self, key: Key, val: u16) -> slog::Result {
+        self.push(key, Value::U64(u64::from(val)))
+    }
+    fn emit_i16(&mut self, key: Key, val: i16) -> slog::Result {
+        self.push(key, Value::I64(i64::from(val)))
+    }
+    fn emit_u32(&mut self, key: Key, val: u32) -> slog::Result {
+        self.push(key, Value::U64(u64::from(val)))
+    }
+    fn emit_i32(&mut self, key: Key, val: i32) -> slog::Result {
+        self.push(key, Value::I64(i64::from(val)))
+    }
+    fn emit_f32(&mut self, key: Key, val: f32) -> slog::Result {
+        self.push(key, Value::F64(f64::from(val)))
+    }
+    fn emit_u64(&mut self, key: Key, val: u64) -> slog::Result {
+        self.push(key, Value::U64(val))
+    }
+    fn emit_i64(&mut self, key: Key, val: i64) -> slog::Result {
+        self.push(key, Value::I64(val))
+    }
+    fn emit_f64(&mut self, key: Key, val: f64) -> slog::Result {
+        self.push(key, Value::F64(val))
+    }
+    fn emit_str(&mut self, key: Key, val: &str) -> slog::Result {
+        self.push(key, Value::Str(val.to_owned()))
+    }
+    fn emit_unit(&mut self, key: Key) -> slog::Result {
+        self.push(key, Value::Unit)
+    }
+    fn emit_none(&mut self, key: Key) -> slog::Result {
+        self.push(key, Value::None)
+    }
+    fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> slog::Result {
+        self.push(key, Value::Str(val.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redactor_works() {
+        let mut redactor = Redactor::default();
+        redactor.add_key("password");
+        redactor.add_pattern(Regex::new(r"Bearer [A-Za-z0-9._-]+").unwrap());
+
+        assert!(redactor.is_sensitive_key("Password"));
+        assert!(!redactor.is_sensitive_key("user"));
+        assert_eq!(
+            redactor.redact_str("Authorization: Bearer abc.def"),
+            "Authorization: [REDACTED]"
+        );
+        assert_eq!(redactor.redact_str("hello"), "hello");
+    }
+}
@@ -0,0 +1,279 @@
+//! Logger which is reloaded when its configuration file changes.
+//!
+//! This module is available only if the `reloadable` feature is enabled.
+use slog::{Drain, Level, Logger, Never, OwnedKVList, Record};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use toml;
+use trackable::error::ErrorKindExt;
+
+use {Build, Config, ErrorKind, LoggerConfig, Result};
+
+/// A builder of `ReloadableLogger`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate slog;
+/// extern crate sloggers;
+///
+/// use sloggers::reloadable::ReloadableLoggerBuilder;
+/// use std::time::Duration;
+///
+/// # fn main() {
+/// let reloadable = ReloadableLoggerBuilder::new("examples/conf/terminal.toml")
+///     .poll_interval(Duration::from_millis(500))
+///     .build()
+///     .unwrap();
+///
+/// let logger = reloadable.logger();
+/// info!(logger, "Hello World!");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ReloadableLoggerBuilder {
+    path: PathBuf,
+    poll_interval: Duration,
+}
+impl ReloadableLoggerBuilder {
+    /// Makes a new `ReloadableLoggerBuilder` instance.
+    ///
+    /// The file specified by `path` must contain a `LoggerConfig` in TOML format.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        ReloadableLoggerBuilder {
+            path: path.as_ref().to_path_buf(),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the interval at which the configuration file is checked for changes.
+    ///
+    /// The default value is one second.
+    pub fn poll_interval(&mut self, interval: Duration) -> &mut Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Builds a `ReloadableLogger`.
+    ///
+    /// This fails if the configuration file cannot be read or contains an invalid configuration.
+    pub fn build(&self) -> Result<ReloadableLogger> {
+        let text = track!(read_config_text(&self.path))?;
+        let inner = track!(build_logger_from_text(&text))?;
+        let current = Arc::new(RwLock::new(inner));
+        let logger = Logger::root(ReloadableDrain(current.clone()), o!());
+
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let poller = Poller {
+            path: self.path.clone(),
+            poll_interval: self.poll_interval,
+            logger: logger.clone(),
+            current: current.clone(),
+            last_text: Some(text),
+            stop_rx,
+        };
+        let thread = thread::spawn(move || poller.run());
+
+        Ok(ReloadableLogger {
+            path: self.path.clone(),
+            logger,
+            current,
+            stop_tx: Some(stop_tx),
+            thread: Some(thread),
+        })
+    }
+}
+
+/// A logger which is rebuilt when its configuration file changes.
+///
+/// A background thread polls the configuration file,
+/// and if the content of the file changes, builds a new logger from it and
+/// atomically replaces the old one. The logger returned by [`logger`] (and its clones)
+/// keeps working across reloads.
+///
+/// If the new configuration is invalid, the old logger keeps running and
+/// the error is reported as an error level log record via the old logger.
+///
+/// The configuration file stops being watched when this instance is dropped.
+///
+/// [`logger`]: ./struct.ReloadableLogger.html#method.logger
+#[derive(Debug)]
+pub struct ReloadableLogger {
+    path: PathBuf,
+    logger: Logger,
+    current: Arc<RwLock<Logger>>,
+    stop_tx: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+impl ReloadableLogger {
+    /// Returns the logger.
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    /// Returns the path of the configuration file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reloads the configuration file immediately.
+    ///
+    /// If it fails, the old logger keeps running.
+    pub fn reload(&self) -> Result<()> {
+        let text = track!(read_config_text(&self.path))?;
+        let logger = track!(build_logger_from_text(&text))?;
+        swap_logger(&self.current, logger);
+        Ok(())
+    }
+}
+impl Drop for ReloadableLogger {
+    fn drop(&mut self) {
+        let _ = self.stop_tx.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct Poller {
+    path: PathBuf,
+    poll_interval: Duration,
+    logger: Logger,
+    current: Arc<RwLock<Logger>>,
+    last_text: Option<String>,
+    stop_rx: mpsc::Receiver<()>,
+}
+impl Poller {
+    fn run(mut self) {
+        while let Err(RecvTimeoutError::Timeout) = self.stop_rx.recv_timeout(self.poll_interval) {
+            self.poll();
+        }
+    }
+
+    fn poll(&mut self) {
+        let text = match read_config_text(&self.path) {
+            Ok(text) => text,
+            Err(e) => {
+                // Reports only once until the file becomes readable again
+                if self.last_text.take().is_some() {
+                    error!(self.logger, "Cannot read the logger configuration file";
+                           "path" => %self.path.display(), "error" => %e);
+                }
+                return;
+            }
+        };
+        if self.last_text.as_ref() == Some(&text) {
+            return;
+        }
+
+        match build_logger_from_text(&text) {
+            Ok(logger) => swap_logger(&self.current, logger),
+            Err(e) => {
+                error!(self.logger, "Cannot reload the logger configuration";
+                       "path" => %self.path.display(), "error" => %e);
+            }
+        }
+        self.last_text = Some(text);
+    }
+}
+
+struct ReloadableDrain(Arc<RwLock<Logger>>);
+impl Drain for ReloadableDrain {
+    type Ok = ();
+    type Err = Never;
+    fn log(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> ::std::result::Result<(), Never> {
+        let logger = self.0.read().unwrap_or_else(|e| e.into_inner());
+        Drain::log(&*logger, record, logger_values)
+    }
+    fn is_enabled(&self, level: Level) -> bool {
+        let logger = self.0.read().unwrap_or_else(|e| e.into_inner());
+        logger.is_enabled(level)
+    }
+}
+
+fn swap_logger(current: &RwLock<Logger>, logger: Logger) {
+    let old = {
+        let mut current = current.write().unwrap_or_else(|e| e.into_inner());
+        ::std::mem::replace(&mut *current, logger)
+    };
+
+    // The old logger is dropped outside of the lock because it may wait for its records to be flushed
+    drop(old);
+}
+
+fn read_config_text(path: &Path) -> Result<String> {
+    let text = track!(
+        fs::read_to_string(path).map_err(::Error::from),
+        "path={:?}",
+        path
+    )?;
+    Ok(text)
+}
+
+fn build_logger_from_text(text: &str) -> Result<Logger> {
+    let config: LoggerConfig =
+        track!(toml::from_str(text).map_err(|e| ErrorKind::Invalid.cause(e)))?;
+    let builder = track!(config.try_to_builder())?;
+    let logger = track!(builder.build())?;
+    Ok(logger)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::thread;
+    use std::time::Duration;
+    use tempdir::TempDir;
+
+    use super::*;
+    use Result;
+
+    #[test]
+    fn reloadable_logger_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let config_path = dir.path().join("logger.toml");
+        let write_config = |level: &str| {
+            let path = dir.path().join("foo.log");
+            let config = format!(
+                "type = \"file\"\nlevel = \"{}\"\npath = {:?}\n",
+                level,
+                path.to_str().unwrap()
+            );
+            fs::write(&config_path, config)
+        };
+
+        write_config("info")?;
+        let reloadable = ReloadableLoggerBuilder::new(&config_path)
+            .poll_interval(Duration::from_millis(10))
+            .build()?;
+        let logger = reloadable.logger().clone();
+
+        debug!(logger, "foo");
+        write_config("debug")?;
+        thread::sleep(Duration::from_millis(100));
+        debug!(logger, "bar");
+
+        fs::write(&config_path, "type = \"unknown\"")?;
+        thread::sleep(Duration::from_millis(100));
+        assert!(reloadable.reload().is_err());
+        debug!(logger, "baz");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(!content.contains("foo"));
+        assert!(content.contains("bar"));
+        assert!(content.contains("Cannot reload the logger configuration"));
+        assert!(content.contains("baz"));
+
+        Ok(())
+    }
+}
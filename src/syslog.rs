@@ -0,0 +1,1162 @@
+//! Syslog logger.
+use chrono::Utc;
+use regex::Regex;
+use slog::{Drain, Level, Logger, Never, OwnedKVList, Record};
+use slog_kvfilter::KVFilter;
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use trackable::error::ErrorKindExt;
+
+use misc::{
+    self, format_timestamp, source_location_logger, with_fields, with_metadata, AsyncDrain,
+    KVCollector,
+};
+use rate_limit::RateLimitDrain;
+use redact::{RedactDrain, Redactor};
+use types::{DroppedRecordCounter, KVFilterParameters, LevelHandle, OverflowStrategy};
+use types::{FieldValue, MetadataFields, RateLimit, Severity, SourceLocation, TimeZone};
+use {Build, Config, Error, ErrorKind, LoggerGuard, Result};
+
+/// A logger builder which build loggers that send log records to a syslog server.
+///
+/// The resulting logger will work asynchronously (the default channel size is 1024).
+///
+/// The connection is opened when the first record is sent,
+/// so building a logger does not fail even if the server is not running.
+/// Log records which cannot be sent (e.g., the server is down) are dropped,
+/// and the connection is re-opened with an exponential backoff (from 100 milliseconds up to 30 seconds).
+#[derive(Debug)]
+pub struct SyslogLoggerBuilder {
+    format: SyslogFormat,
+    source_location: SourceLocation,
+    source_location_key: String,
+    metadata: MetadataFields,
+    fields: Vec<(String, FieldValue)>,
+    redactor: Redactor,
+    rate_limit: Option<RateLimit>,
+    timezone: TimeZone,
+    destination: Destination,
+    framing: Framing,
+    facility: Facility,
+    ident: Option<String>,
+    hostname: Option<String>,
+    structured_data_id: String,
+    level: Severity,
+    channel_size: usize,
+    overflow_strategy: OverflowStrategy,
+    dropped_records: DroppedRecordCounter,
+    kvfilterparameters: Option<KVFilterParameters>,
+}
+impl SyslogLoggerBuilder {
+    /// Makes a new `SyslogLoggerBuilder` instance.
+    pub fn new() -> Self {
+        SyslogLoggerBuilder {
+            format: SyslogFormat::default(),
+            source_location: SourceLocation::default(),
+            source_location_key: default_source_location_key(),
+            metadata: MetadataFields::default(),
+            fields: Vec::new(),
+            redactor: Redactor::default(),
+            rate_limit: None,
+            timezone: TimeZone::default(),
+            destination: Destination::default(),
+            framing: Framing::default(),
+            facility: Facility::default(),
+            ident: None,
+            hostname: None,
+            structured_data_id: default_structured_data_id(),
+            level: Severity::default(),
+            channel_size: 1024,
+            overflow_strategy: OverflowStrategy::default(),
+            dropped_records: DroppedRecordCounter::new(),
+            kvfilterparameters: None,
+        }
+    }
+
+    /// Sets the syslog message format.
+    pub fn format(&mut self, format: SyslogFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the source code location type this logger will use.
+    pub fn source_location(&mut self, source_location: SourceLocation) -> &mut Self {
+        self.source_location = source_location;
+        self
+    }
+
+    /// Sets the key under which the source code location is added to log records.
+    ///
+    /// The default value is `"module"`.
+    pub fn source_location_key(&mut self, key: &str) -> &mut Self {
+        self.source_location_key = key.to_owned();
+        self
+    }
+
+    /// Sets the metadata fields (e.g., process ID and thread name) added to every log record.
+    pub fn metadata(&mut self, fields: MetadataFields) -> &mut Self {
+        self.metadata = fields;
+        self
+    }
+
+    /// Adds a static key/value pair to every log record.
+    pub fn field<V: Into<FieldValue>>(&mut self, key: &str, value: V) -> &mut Self {
+        self.fields.push((key.to_owned(), value.into()));
+        self
+    }
+
+    /// Adds a key whose values are replaced with `"[REDACTED]"` in log records.
+    ///
+    /// Keys are compared case-insensitively.
+    pub fn redact_key(&mut self, key: &str) -> &mut Self {
+        self.redactor.add_key(key);
+        self
+    }
+
+    /// Adds a pattern whose matches in the messages and the string values of log records
+    /// are replaced with `"[REDACTED]"`.
+    pub fn redact_pattern(&mut self, pattern: Regex) -> &mut Self {
+        self.redactor.add_pattern(pattern);
+        self
+    }
+
+    /// Sets the rate limit of log records.
+    ///
+    /// See the documentation of [`RateLimit`] for more details.
+    ///
+    /// [`RateLimit`]: ../types/struct.RateLimit.html
+    pub fn rate_limit(&mut self, limit: RateLimit) -> &mut Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Sets the time zone which this logger will use.
+    pub fn timezone(&mut self, timezone: TimeZone) -> &mut Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Sets the destination to which log records will be sent.
+    pub fn destination(&mut self, destination: Destination) -> &mut Self {
+        self.destination = destination;
+        self
+    }
+
+    /// Sets the message framing used when the destination is TCP.
+    ///
+    /// This setting is ignored for the other destinations,
+    /// because each message is sent as a single datagram.
+    pub fn framing(&mut self, framing: Framing) -> &mut Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Sets the syslog facility.
+    pub fn facility(&mut self, facility: Facility) -> &mut Self {
+        self.facility = facility;
+        self
+    }
+
+    /// Sets the identity of this program (i.e., `TAG` of RFC 3164 and `APP-NAME` of RFC 5424).
+    ///
+    /// The default value is the file name of the current executable.
+    pub fn ident(&mut self, ident: &str) -> &mut Self {
+        self.ident = Some(ident.to_owned());
+        self
+    }
+
+    /// Sets the host name written in each message.
+    ///
+    /// The default value is the host name of the running machine.
+    pub fn hostname(&mut self, hostname: &str) -> &mut Self {
+        self.hostname = Some(hostname.to_owned());
+        self
+    }
+
+    /// Sets the `SD-ID` under which key/value pairs are written as RFC 5424 structured data.
+    ///
+    /// The default value is `"sloggers@32473"`.
+    pub fn structured_data_id(&mut self, id: &str) -> &mut Self {
+        self.structured_data_id = id.to_owned();
+        self
+    }
+
+    /// Sets the log level of this logger.
+    pub fn level(&mut self, severity: Severity) -> &mut Self {
+        self.level = severity;
+        self
+    }
+
+    /// Sets the size of the asynchronous channel of this logger.
+    pub fn channel_size(&mut self, channel_size: usize) -> &mut Self {
+        self.channel_size = channel_size;
+        self
+    }
+
+    /// Sets the behavior of this logger when its asynchronous channel is full.
+    pub fn overflow_strategy(&mut self, overflow_strategy: OverflowStrategy) -> &mut Self {
+        self.overflow_strategy = overflow_strategy;
+        self
+    }
+
+    /// Returns the counter of the log records dropped due to overflows of the asynchronous channel.
+    ///
+    /// The counter is shared by all the loggers built by this builder.
+    pub fn dropped_record_counter(&self) -> DroppedRecordCounter {
+        self.dropped_records.clone()
+    }
+
+    /// Sets [`KVFilter`].
+    ///
+    /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
+    pub fn kvfilter(&mut self, parameters: KVFilterParameters) -> &mut Self {
+        self.kvfilterparameters = Some(parameters);
+        self
+    }
+
+    /// Builds a logger and a handle for changing the log level of the logger at runtime.
+    pub fn build_with_level_handle(&self) -> Result<(Logger, LevelHandle)> {
+        track!(self.build_logger(None))
+    }
+
+    pub(crate) fn build_logger(
+        &self,
+        guard: Option<&mut LoggerGuard>,
+    ) -> Result<(Logger, LevelHandle)> {
+        if let Some(limit) = self.rate_limit {
+            track!(limit.validate())?;
+        }
+        #[cfg(not(unix))]
+        {
+            if let Destination::Unix(ref path) = self.destination {
+                track_panic!(
+                    ErrorKind::Invalid,
+                    "Unix domain sockets are not supported on this platform: {:?}",
+                    path
+                );
+            }
+        }
+        let level = LevelHandle::new(self.level);
+        let ident = self
+            .ident
+            .clone()
+            .or_else(misc::executable_name)
+            .unwrap_or_else(|| "sloggers".to_owned());
+        let hostname = self
+            .hostname
+            .clone()
+            .or_else(misc::hostname)
+            .unwrap_or_else(|| "-".to_owned());
+        let drain = SyslogDrain {
+            format: self.format,
+            timezone: self.timezone,
+            framing: self.framing,
+            facility: self.facility,
+            ident,
+            hostname,
+            structured_data_id: self.structured_data_id.clone(),
+            pid: process::id(),
+            destination: self.destination.clone(),
+            connection: RefCell::new(ConnectionState {
+                connection: None,
+                retry_at: None,
+                backoff: MIN_RECONNECT_BACKOFF,
+            }),
+        };
+        let logger = self.build_with_drain(drain, &level, guard);
+        let logger = with_fields(logger, &self.fields);
+        Ok((with_metadata(logger, &self.metadata), level))
+    }
+
+    fn build_with_drain<D>(
+        &self,
+        drain: D,
+        level: &LevelHandle,
+        mut guard: Option<&mut LoggerGuard>,
+    ) -> Logger
+    where
+        D: Drain + Send + 'static,
+        D::Err: Debug,
+    {
+        let drain = RedactDrain::new(drain, self.redactor.clone());
+
+        // The worker thread must be joined after the pending summaries of the rate limit are outputted
+        let mut async_guard = guard.as_ref().map(|_| LoggerGuard::new());
+
+        // async inside, level and key value filters outside for speed
+        let drain = AsyncDrain::new(
+            drain.fuse(),
+            self.channel_size,
+            self.overflow_strategy,
+            self.dropped_records.clone(),
+            async_guard.as_mut(),
+        )
+        .fuse();
+
+        // The rate limit is applied after the filters, so that filtered out records do not use it up
+        let drain = RateLimitDrain::new(drain, self.rate_limit, guard.as_deref_mut());
+        if let (Some(guard), Some(async_guard)) = (guard, async_guard) {
+            guard.merge(async_guard);
+        }
+
+        if let Some(ref p) = self.kvfilterparameters {
+            let kvdrain = KVFilter::new(drain, p.severity.as_level())
+                .always_suppress_any(p.always_suppress_any.clone())
+                .only_pass_any_on_all_keys(p.only_pass_any_on_all_keys.clone())
+                .always_suppress_on_regex(p.always_suppress_on_regex.clone())
+                .only_pass_on_regex(p.only_pass_on_regex.clone());
+
+            let drain = level.set_level_filter(kvdrain.fuse());
+
+            source_location_logger(
+                drain.fuse(),
+                self.source_location,
+                &self.source_location_key,
+            )
+        } else {
+            let drain = level.set_level_filter(drain.fuse());
+
+            source_location_logger(
+                drain.fuse(),
+                self.source_location,
+                &self.source_location_key,
+            )
+        }
+    }
+}
+impl Default for SyslogLoggerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Build for SyslogLoggerBuilder {
+    fn build(&self) -> Result<Logger> {
+        let (logger, _) = track!(self.build_with_level_handle())?;
+        Ok(logger)
+    }
+
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let mut guard = LoggerGuard::new();
+        let (logger, _) = track!(self.build_logger(Some(&mut guard)))?;
+        Ok((logger, guard))
+    }
+}
+
+/// The format of syslog messages.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::syslog::SyslogFormat;
+///
+/// assert_eq!(SyslogFormat::default(), SyslogFormat::Rfc3164);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogFormat {
+    /// [RFC 3164](https://tools.ietf.org/html/rfc3164) (BSD syslog) format.
+    ///
+    /// Key/value pairs are appended to the message.
+    #[default]
+    Rfc3164,
+
+    /// [RFC 5424](https://tools.ietf.org/html/rfc5424) format.
+    ///
+    /// Key/value pairs are written as structured data.
+    Rfc5424,
+}
+impl FromStr for SyslogFormat {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "rfc3164" => Ok(SyslogFormat::Rfc3164),
+            "rfc5424" => Ok(SyslogFormat::Rfc5424),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined syslog format: {:?}", s),
+        }
+    }
+}
+
+/// The destination to which log records will be sent.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::syslog::Destination;
+///
+/// assert_eq!(Destination::default(), Destination::Unix("/dev/log".into()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Destination {
+    /// Unix domain datagram socket (e.g., `/dev/log`).
+    Unix(PathBuf),
+
+    /// Remote UDP endpoint (e.g., `"127.0.0.1:514"`).
+    Udp(String),
+
+    /// Remote TCP endpoint (e.g., `"127.0.0.1:514"`).
+    Tcp(String),
+}
+impl Default for Destination {
+    fn default() -> Self {
+        Destination::Unix(PathBuf::from("/dev/log"))
+    }
+}
+
+/// The framing of messages sent over TCP.
+///
+/// See [RFC 6587](https://tools.ietf.org/html/rfc6587) for more details.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::syslog::Framing;
+///
+/// assert_eq!(Framing::default(), Framing::OctetCounting);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Framing {
+    /// Each message is prefixed with its length in bytes.
+    #[default]
+    OctetCounting,
+
+    /// Each message is terminated by a newline.
+    NonTransparent,
+}
+impl FromStr for Framing {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "octet_counting" => Ok(Framing::OctetCounting),
+            "non_transparent" => Ok(Framing::NonTransparent),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined syslog framing: {:?}", s),
+        }
+    }
+}
+
+/// Syslog facility.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::syslog::Facility;
+///
+/// assert_eq!(Facility::default(), Facility::User);
+/// ```
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Facility {
+    Kern,
+    #[default]
+    User,
+    Mail,
+    Daemon,
+    Auth,
+    Syslog,
+    Lpr,
+    News,
+    Uucp,
+    Cron,
+    Authpriv,
+    Ftp,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+impl Facility {
+    fn code(self) -> u8 {
+        match self {
+            Facility::Kern => 0,
+            Facility::User => 1,
+            Facility::Mail => 2,
+            Facility::Daemon => 3,
+            Facility::Auth => 4,
+            Facility::Syslog => 5,
+            Facility::Lpr => 6,
+            Facility::News => 7,
+            Facility::Uucp => 8,
+            Facility::Cron => 9,
+            Facility::Authpriv => 10,
+            Facility::Ftp => 11,
+            Facility::Local0 => 16,
+            Facility::Local1 => 17,
+            Facility::Local2 => 18,
+            Facility::Local3 => 19,
+            Facility::Local4 => 20,
+            Facility::Local5 => 21,
+            Facility::Local6 => 22,
+            Facility::Local7 => 23,
+        }
+    }
+}
+impl FromStr for Facility {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "kern" => Ok(Facility::Kern),
+            "user" => Ok(Facility::User),
+            "mail" => Ok(Facility::Mail),
+            "daemon" => Ok(Facility::Daemon),
+            "auth" => Ok(Facility::Auth),
+            "syslog" => Ok(Facility::Syslog),
+            "lpr" => Ok(Facility::Lpr),
+            "news" => Ok(Facility::News),
+            "uucp" => Ok(Facility::Uucp),
+            "cron" => Ok(Facility::Cron),
+            "authpriv" => Ok(Facility::Authpriv),
+            "ftp" => Ok(Facility::Ftp),
+            "local0" => Ok(Facility::Local0),
+            "local1" => Ok(Facility::Local1),
+            "local2" => Ok(Facility::Local2),
+            "local3" => Ok(Facility::Local3),
+            "local4" => Ok(Facility::Local4),
+            "local5" => Ok(Facility::Local5),
+            "local6" => Ok(Facility::Local6),
+            "local7" => Ok(Facility::Local7),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined syslog facility: {:?}", s),
+        }
+    }
+}
+
+fn severity_code(level: Level) -> u8 {
+    match level {
+        Level::Critical => 2,
+        Level::Error => 3,
+        Level::Warning => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+#[derive(Debug)]
+enum Connection {
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+impl Connection {
+    fn open(destination: &Destination) -> Result<Self> {
+        match *destination {
+            #[cfg(unix)]
+            Destination::Unix(ref path) => {
+                let socket = track!(UnixDatagram::unbound().map_err(Error::from))?;
+                track!(socket.connect(path).map_err(Error::from); path)?;
+                Ok(Connection::Unix(socket))
+            }
+            #[cfg(not(unix))]
+            Destination::Unix(ref path) => track_panic!(
+                ErrorKind::Invalid,
+                "Unix domain sockets are not supported on this platform: {:?}",
+                path
+            ),
+            Destination::Udp(ref addr) => {
+                let addr = track!(resolve(addr))?;
+                let local_addr = if addr.is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                };
+                let socket = track!(UdpSocket::bind(local_addr).map_err(Error::from))?;
+                track!(socket.connect(addr).map_err(Error::from); addr)?;
+                Ok(Connection::Udp(socket))
+            }
+            Destination::Tcp(ref addr) => {
+                let addr = track!(resolve(addr))?;
+                let stream = track!(TcpStream::connect(addr).map_err(Error::from); addr)?;
+                Ok(Connection::Tcp(stream))
+            }
+        }
+    }
+
+    fn send(&mut self, message: &[u8], framing: Framing) -> io::Result<()> {
+        match *self {
+            #[cfg(unix)]
+            Connection::Unix(ref socket) => socket.send(message).map(|_| ()),
+            Connection::Udp(ref socket) => socket.send(message).map(|_| ()),
+            Connection::Tcp(ref mut stream) => {
+                match framing {
+                    Framing::OctetCounting => {
+                        write!(stream, "{} ", message.len())?;
+                        stream.write_all(message)?;
+                    }
+                    Framing::NonTransparent => {
+                        stream.write_all(message)?;
+                        stream.write_all(b"\n")?;
+                    }
+                }
+                stream.flush()
+            }
+        }
+    }
+}
+
+fn resolve(addr: &str) -> Result<SocketAddr> {
+    let mut addrs = track!(addr.to_socket_addrs().map_err(Error::from); addr)?;
+    let addr = track_assert_some!(
+        addrs.next(),
+        ErrorKind::Invalid,
+        "Cannot resolve address: {:?}",
+        addr
+    );
+    Ok(addr)
+}
+
+const MIN_RECONNECT_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+struct ConnectionState {
+    connection: Option<Connection>,
+    retry_at: Option<Instant>,
+    backoff: Duration,
+}
+impl ConnectionState {
+    fn is_waiting(&self) -> bool {
+        self.connection.is_none() && self.retry_at.map_or(false, |t| Instant::now() < t)
+    }
+
+    fn connect(&mut self, destination: &Destination) -> io::Result<&mut Connection> {
+        if self.connection.is_none() {
+            let connection = Connection::open(destination)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            self.connection = Some(connection);
+        }
+        Ok(self.connection.as_mut().expect("Never fails"))
+    }
+
+    fn on_success(&mut self) {
+        self.retry_at = None;
+        self.backoff = MIN_RECONNECT_BACKOFF;
+    }
+
+    fn on_failure(&mut self) {
+        self.connection = None;
+        self.retry_at = Some(Instant::now() + self.backoff);
+        self.backoff = cmp::min(self.backoff * 2, MAX_RECONNECT_BACKOFF);
+    }
+}
+
+struct SyslogDrain {
+    format: SyslogFormat,
+    timezone: TimeZone,
+    framing: Framing,
+    facility: Facility,
+    ident: String,
+    hostname: String,
+    structured_data_id: String,
+    pid: u32,
+    destination: Destination,
+    connection: RefCell<ConnectionState>,
+}
+impl SyslogDrain {
+    fn format_message(&self, record: &Record, logger_values: &OwnedKVList) -> io::Result<String> {
+        let priority =
+            u32::from(self.facility.code()) * 8 + u32::from(severity_code(record.level()));
+        let pairs = KVCollector::collect(record, logger_values)?;
+        let message = match self.format {
+            SyslogFormat::Rfc3164 => {
+                let timestamp = self.timestamp("%b %e %H:%M:%S");
+                let mut message = format!(
+                    "<{}>{} {} {}[{}]: {}",
+                    priority,
+                    timestamp,
+                    self.hostname,
+                    self.ident,
+                    self.pid,
+                    record.msg()
+                );
+                for (key, value) in pairs {
+                    message.push_str(&format!(", {}: {}", key, value));
+                }
+                message
+            }
+            SyslogFormat::Rfc5424 => {
+                let timestamp = self.timestamp("%Y-%m-%dT%H:%M:%S%.6f%:z");
+                let structured_data = if pairs.is_empty() {
+                    "-".to_owned()
+                } else {
+                    let mut sd = format!("[{}", self.structured_data_id);
+                    for (key, value) in pairs {
+                        let name = sd_param_name(&key);
+                        if name.is_empty() {
+                            // The key has no characters allowed in parameter names
+                            continue;
+                        }
+                        sd.push_str(&format!(" {}=\"{}\"", name, sd_param_value(&value)));
+                    }
+                    sd.push(']');
+                    sd
+                };
+                format!(
+                    "<{}>1 {} {} {} {} - {} {}",
+                    priority,
+                    timestamp,
+                    header_field(&self.hostname, 255),
+                    header_field(&self.ident, 48),
+                    self.pid,
+                    structured_data,
+                    record.msg()
+                )
+            }
+        };
+        Ok(message)
+    }
+
+    fn timestamp(&self, format: &str) -> String {
+        format_timestamp(format, self.timezone, Utc::now())
+    }
+}
+impl SyslogDrain {
+    fn send(&self, record: &Record, logger_values: &OwnedKVList) -> io::Result<()> {
+        let message = self.format_message(record, logger_values)?;
+        let mut state = self.connection.borrow_mut();
+        if state.is_waiting() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "Waiting for reconnection",
+            ));
+        }
+        let result = state
+            .connect(&self.destination)
+            .and_then(|c| c.send(message.as_bytes(), self.framing));
+        if result.is_ok() {
+            state.on_success();
+        } else {
+            // Reconnects after the backoff
+            state.on_failure();
+        }
+        result
+    }
+}
+impl Drain for SyslogDrain {
+    type Ok = ();
+    type Err = Never;
+    fn log(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+    ) -> ::std::result::Result<(), Never> {
+        // Errors must not reach `Fuse`, which would panic and stop the worker thread for good,
+        // so the record is dropped instead
+        let _ = self.send(record, logger_values);
+        Ok(())
+    }
+}
+
+// Makes a HOSTNAME or APP-NAME field of RFC 5424, which consists of printable US-ASCII characters
+fn header_field(value: &str, max_len: usize) -> String {
+    let field = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect::<String>();
+    if field.is_empty() {
+        "-".to_owned()
+    } else {
+        field
+    }
+}
+
+fn sd_param_name(name: &str) -> String {
+    name.chars()
+        .filter(|&c| c.is_ascii_graphic() && c != '=' && c != ']' && c != '"')
+        .take(32)
+        .collect()
+}
+
+fn sd_param_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '"' || c == '\\' || c == ']' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The configuration of `SyslogLoggerBuilder`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyslogLoggerConfig {
+    /// Log level.
+    #[serde(default)]
+    pub level: Severity,
+
+    /// Syslog message format.
+    #[serde(default)]
+    pub format: SyslogFormat,
+
+    /// Source code location
+    #[serde(default)]
+    pub source_location: SourceLocation,
+
+    /// The key under which the source code location is added to log records.
+    #[serde(default = "default_source_location_key")]
+    pub source_location_key: String,
+
+    /// Metadata fields added to every log record.
+    #[serde(default)]
+    pub metadata: MetadataFields,
+
+    /// Static key/value pairs added to every log record.
+    ///
+    /// `${NAME}` in string values is replaced with the environment variable `NAME`.
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldValue>,
+
+    /// Keys whose values are replaced with `"[REDACTED]"` (compared case-insensitively).
+    #[serde(default)]
+    pub redact_keys: Vec<String>,
+
+    /// Regular expressions whose matches in messages and string values are replaced with `"[REDACTED]"`.
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+
+    /// Rate limit of log records.
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+
+    /// Time Zone.
+    #[serde(default)]
+    pub timezone: TimeZone,
+
+    /// Destination of syslog messages.
+    #[serde(default)]
+    pub destination: Destination,
+
+    /// Message framing used for TCP destinations.
+    #[serde(default)]
+    pub framing: Framing,
+
+    /// Syslog facility.
+    #[serde(default)]
+    pub facility: Facility,
+
+    /// Identity of this program.
+    ///
+    /// If omitted, the file name of the current executable will be used.
+    #[serde(default)]
+    pub ident: Option<String>,
+
+    /// Host name.
+    ///
+    /// If omitted, the host name of the running machine will be used.
+    #[serde(default)]
+    pub hostname: Option<String>,
+
+    /// `SD-ID` of the structured data (RFC 5424 only).
+    #[serde(default = "default_structured_data_id")]
+    pub structured_data_id: String,
+
+    /// Asynchronous channel size
+    #[serde(default = "default_channel_size")]
+    pub channel_size: usize,
+
+    /// Behavior when the asynchronous channel is full.
+    #[serde(default)]
+    pub overflow_strategy: OverflowStrategy,
+
+    /// [`KVFilter`] parameters.
+    ///
+    /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
+    #[serde(default)]
+    pub kvfilter: Option<KVFilterParameters>,
+}
+impl Config for SyslogLoggerConfig {
+    type Builder = SyslogLoggerBuilder;
+    fn try_to_builder(&self) -> Result<Self::Builder> {
+        let mut builder = SyslogLoggerBuilder::new();
+        builder.level(self.level);
+        builder.format(self.format);
+        builder.source_location(self.source_location);
+        builder.source_location_key(&self.source_location_key);
+        builder.metadata(self.metadata.clone());
+        for (key, value) in &self.fields {
+            builder.field(key, track!(value.expand_env(), "key={:?}", key)?);
+        }
+        for key in &self.redact_keys {
+            builder.redact_key(key);
+        }
+        for pattern in &self.redact_patterns {
+            let pattern = track!(Regex::new(pattern).map_err(|e| ErrorKind::Invalid.cause(e)))?;
+            builder.redact_pattern(pattern);
+        }
+        if let Some(limit) = self.rate_limit {
+            builder.rate_limit(limit);
+        }
+        builder.timezone(self.timezone);
+        builder.destination(self.destination.clone());
+        builder.framing(self.framing);
+        builder.facility(self.facility);
+        if let Some(ref ident) = self.ident {
+            builder.ident(ident);
+        }
+        if let Some(ref hostname) = self.hostname {
+            builder.hostname(hostname);
+        }
+        builder.structured_data_id(&self.structured_data_id);
+        builder.channel_size(self.channel_size);
+        builder.overflow_strategy(self.overflow_strategy);
+        if let Some(ref p) = self.kvfilter {
+            builder.kvfilter(p.clone());
+        }
+        Ok(builder)
+    }
+}
+impl Default for SyslogLoggerConfig {
+    fn default() -> Self {
+        SyslogLoggerConfig {
+            level: Severity::default(),
+            format: SyslogFormat::default(),
+            source_location: SourceLocation::default(),
+            source_location_key: default_source_location_key(),
+            metadata: MetadataFields::default(),
+            fields: BTreeMap::new(),
+            redact_keys: Vec::new(),
+            redact_patterns: Vec::new(),
+            rate_limit: None,
+            timezone: TimeZone::default(),
+            destination: Destination::default(),
+            framing: Framing::default(),
+            facility: Facility::default(),
+            ident: None,
+            hostname: None,
+            structured_data_id: default_structured_data_id(),
+            channel_size: default_channel_size(),
+            overflow_strategy: OverflowStrategy::default(),
+            kvfilter: None,
+        }
+    }
+}
+
+fn default_structured_data_id() -> String {
+    "sloggers@32473".to_owned()
+}
+
+fn default_source_location_key() -> String {
+    "module".to_owned()
+}
+
+fn default_channel_size() -> usize {
+    1024
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::net::UdpSocket;
+    use std::str;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use test_util::lock_global_state;
+    use {Build, LoggerConfig, Result};
+
+    #[test]
+    fn rfc5424_over_udp_works() -> Result<()> {
+        let server = UdpSocket::bind("127.0.0.1:0")?;
+        server.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let logger = SyslogLoggerBuilder::new()
+            .format(SyslogFormat::Rfc5424)
+            .destination(Destination::Udp(server.local_addr()?.to_string()))
+            .facility(Facility::Local0)
+            .ident("foo")
+            .hostname("bar")
+            .source_location(SourceLocation::None)
+            .build()?;
+
+        warn!(logger, "hello"; "key" => "a\"b");
+
+        let mut buf = [0; 1024];
+        let size = server.recv(&mut buf)?;
+        let message = str::from_utf8(&buf[..size]).unwrap();
+        assert!(message.starts_with("<132>1 "));
+        assert!(message.contains(" bar foo "));
+        assert!(message.ends_with(" - [sloggers@32473 key=\"a\\\"b\"] hello"));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn missing_unix_socket_does_not_fail_build() -> Result<()> {
+        let logger = SyslogLoggerBuilder::new()
+            .destination(Destination::Unix("/non_existent_dir/log".into()))
+            .build()?;
+        info!(logger, "lost");
+
+        Ok(())
+    }
+
+    #[test]
+    fn rfc5424_header_fields_are_sanitized() {
+        assert_eq!(header_field("foo bar\u{3042}", 48), "foobar");
+        assert_eq!(header_field(" \t", 48), "-");
+        assert_eq!(header_field(&"a".repeat(300), 255).len(), 255);
+        assert_eq!(sd_param_name("\u{3042}"), "");
+
+        let drain = SyslogDrain {
+            format: SyslogFormat::Rfc5424,
+            timezone: TimeZone::Utc,
+            framing: Framing::default(),
+            facility: Facility::default(),
+            ident: "my app".to_owned(),
+            hostname: String::new(),
+            structured_data_id: "id@1".to_owned(),
+            pid: 1,
+            destination: Destination::default(),
+            connection: RefCell::new(ConnectionState {
+                connection: None,
+                retry_at: None,
+                backoff: MIN_RECONNECT_BACKOFF,
+            }),
+        };
+        let message = drain
+            .format_message(
+                &record!(
+                    Level::Info,
+                    "",
+                    &format_args!("hello"),
+                    b!("\u{3042}" => 1, "a" => 2)
+                ),
+                &o!().into(),
+            )
+            .expect("Never fails");
+        assert!(
+            message.ends_with(" - myapp 1 - [id@1 a=\"2\"] hello"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn unreachable_destination_does_not_stop_logging() -> Result<()> {
+        // Reserves a port, and closes it so that sending to the port fails
+        let addr = UdpSocket::bind("127.0.0.1:0")?.local_addr()?;
+        let logger = SyslogLoggerBuilder::new()
+            .destination(Destination::Udp(addr.to_string()))
+            .source_location(SourceLocation::None)
+            .build()?;
+        for i in 0..5 {
+            info!(logger, "lost"; "i" => i);
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        // A record may be dropped due to the error of a preceding datagram
+        let server = UdpSocket::bind(addr)?;
+        server.set_read_timeout(Some(Duration::from_millis(100)))?;
+        let mut buf = [0; 1024];
+        let mut received = false;
+        for _ in 0..50 {
+            info!(logger, "hello");
+            if let Ok(size) = server.recv(&mut buf) {
+                if str::from_utf8(&buf[..size]).unwrap().ends_with("]: hello") {
+                    received = true;
+                    break;
+                }
+            }
+        }
+        assert!(received);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rfc3164_over_udp_works() -> Result<()> {
+        let server = UdpSocket::bind("127.0.0.1:0")?;
+        server.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let logger = SyslogLoggerBuilder::new()
+            .destination(Destination::Udp(server.local_addr()?.to_string()))
+            .ident("foo")
+            .hostname("bar")
+            .source_location(SourceLocation::None)
+            .build()?;
+
+        info!(logger, "hello"; "key" => 1);
+
+        let mut buf = [0; 1024];
+        let size = server.recv(&mut buf)?;
+        let message = str::from_utf8(&buf[..size]).unwrap();
+        assert!(message.starts_with("<14>"));
+        assert!(message.contains(" bar foo["));
+        assert!(message.ends_with("]: hello, key: 1"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn syslog_overflow_strategy_works() -> Result<()> {
+        let server = UdpSocket::bind("127.0.0.1:0")?;
+        server.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let mut builder = SyslogLoggerBuilder::new();
+        builder
+            .destination(Destination::Udp(server.local_addr()?.to_string()))
+            .source_location(SourceLocation::None)
+            .channel_size(1)
+            .overflow_strategy(OverflowStrategy::Block);
+        let counter = builder.dropped_record_counter();
+
+        let logger = builder.build()?;
+        for i in 0..100 {
+            info!(logger, "record {}", i);
+        }
+        drop(logger);
+        assert_eq!(counter.get(), 0);
+
+        let mut buf = [0; 1024];
+        for i in 0..100 {
+            let size = server.recv(&mut buf)?;
+            let message = str::from_utf8(&buf[..size]).unwrap();
+            assert!(message.ends_with(&format!("]: record {}", i)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn syslog_config_env_override_works() -> Result<()> {
+        let _lock = lock_global_state();
+        let mut config = LoggerConfig::Syslog(SyslogLoggerConfig::default());
+
+        // `FORMAT` is for the other loggers, so it is not parsed as `SyslogFormat`
+        env::set_var("SLOGGERS_SYSLOG_TEST_FORMAT", "json");
+        env::set_var("SLOGGERS_SYSLOG_TEST_SYSLOG_FORMAT", "rfc5424");
+        let result = config.apply_env("SLOGGERS_SYSLOG_TEST");
+        env::remove_var("SLOGGERS_SYSLOG_TEST_FORMAT");
+        env::remove_var("SLOGGERS_SYSLOG_TEST_SYSLOG_FORMAT");
+        track!(result)?;
+
+        match config {
+            LoggerConfig::Syslog(ref c) => assert_eq!(c.format, SyslogFormat::Rfc5424),
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use slog::{Drain, OwnedKVList, Record};
+use std::cell::RefCell;
+use std::io;
+use std::str::FromStr;
+
+use misc::{KVCollector, Timestamper};
+use {Error, ErrorKind, Result};
+
+/// The key under which `ThreadDrain` stores the name of the logging thread.
+pub const THREAD_KEY: &str = "thread";
+
+/// A parsed line template of `Format::Template`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+impl Template {
+    /// Returns `true` if this template contains the `{thread}` placeholder.
+    pub fn uses_thread(&self) -> bool {
+        self.parts.contains(&Part::Thread)
+    }
+
+    fn render(
+        &self,
+        record: &Record,
+        logger_values: &OwnedKVList,
+        timestamper: &Timestamper,
+    ) -> io::Result<String> {
+        let pairs = KVCollector::collect(record, logger_values)?;
+        let lookup = |key: &str| {
+            pairs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map_or("", |(_, v)| v.as_str())
+        };
+
+        let mut line = String::new();
+        for part in &self.parts {
+            match *part {
+                Part::Literal(ref s) => line.push_str(s),
+                Part::Timestamp => {
+                    if let Some(timestamp) = timestamper.now() {
+                        line.push_str(&timestamp.to_string());
+                    }
+                }
+                Part::Level => line.push_str(record.level().as_short_str()),
+                Part::LevelLong => line.push_str(record.level().as_str()),
+                Part::Message => line.push_str(&record.msg().to_string()),
+                Part::Module => line.push_str(record.module()),
+                Part::File => line.push_str(record.file()),
+                Part::Line => line.push_str(&record.line().to_string()),
+                Part::Thread => line.push_str(lookup(THREAD_KEY)),
+                Part::Key(ref key) => line.push_str(lookup(key)),
+                Part::Kv => {
+                    let mut first = true;
+                    for (k, v) in &pairs {
+                        if self.is_hidden_key(k) {
+                            continue;
+                        }
+                        if !first {
+                            line.push(' ');
+                        }
+                        first = false;
+                        line.push_str(k);
+                        line.push('=');
+                        line.push_str(v);
+                    }
+                }
+            }
+        }
+        line.push('\n');
+        Ok(line)
+    }
+
+    // Keys which are outputted by other placeholders are excluded from `{kv}`
+    fn is_hidden_key(&self, key: &str) -> bool {
+        self.parts.iter().any(|part| match *part {
+            Part::Thread => key == THREAD_KEY,
+            Part::Key(ref k) => k == key,
+            _ => false,
+        })
+    }
+}
+impl FromStr for Template {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                track_panic!(ErrorKind::Invalid, "Unclosed placeholder: {:?}", s)
+                            }
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(literal.clone()));
+                        literal.clear();
+                    }
+                    parts.push(track!(Part::from_placeholder(&name), "template={:?}", s)?);
+                }
+                '}' => track_panic!(ErrorKind::Invalid, "Unmatched '}}': {:?}", s),
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Template { parts })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Timestamp,
+    Level,
+    LevelLong,
+    Message,
+    Module,
+    File,
+    Line,
+    Thread,
+    Kv,
+    Key(String),
+}
+impl Part {
+    fn from_placeholder(name: &str) -> Result<Self> {
+        let part = match name {
+            "ts" => Part::Timestamp,
+            "level" => Part::Level,
+            "level_long" => Part::LevelLong,
+            "msg" => Part::Message,
+            "module" => Part::Module,
+            "file" => Part::File,
+            "line" => Part::Line,
+            "thread" => Part::Thread,
+            "kv" => Part::Kv,
+            _ if name.starts_with("key:") && name.len() > 4 => Part::Key(name[4..].to_owned()),
+            _ => track_panic!(ErrorKind::Invalid, "Unknown placeholder: {:?}", name),
+        };
+        Ok(part)
+    }
+}
+
+/// A drain which outputs log records formatted by a `Template`.
+pub struct TemplateDrain<W: io::Write> {
+    writer: RefCell<W>,
+    template: Template,
+    timestamper: Timestamper,
+}
+impl<W: io::Write> TemplateDrain<W> {
+    pub fn new(writer: W, template: Template, timestamper: Timestamper) -> Self {
+        TemplateDrain {
+            writer: RefCell::new(writer),
+            template,
+            timestamper,
+        }
+    }
+}
+impl<W: io::Write> Drain for TemplateDrain<W> {
+    type Ok = ();
+    type Err = io::Error;
+    fn log(&self, record: &Record, logger_values: &OwnedKVList) -> io::Result<()> {
+        let line = self
+            .template
+            .render(record, logger_values, &self.timestamper)?;
+        let mut writer = self.writer.borrow_mut();
+        writer.write_all(line.as_bytes())?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_parse_works() {
+        let template: Template = "{ts} [{level}] {{{msg}}} {key:foo}".parse().unwrap();
+        assert_eq!(
+            template.parts,
+            vec![
+                Part::Timestamp,
+                Part::Literal(" [".to_owned()),
+                Part::Level,
+                Part::Literal("] {".to_owned()),
+                Part::Message,
+                Part::Literal("} ".to_owned()),
+                Part::Key("foo".to_owned()),
+            ]
+        );
+        assert!(!template.uses_thread());
+
+        assert!("{foo}".parse::<Template>().is_err());
+        assert!("{msg".parse::<Template>().is_err());
+        assert!("msg}".parse::<Template>().is_err());
+        assert!("{key:}".parse::<Template>().is_err());
+    }
+}
@@ -1,15 +1,28 @@
 //! Terminal logger.
-use slog::{self, Drain, FnValue, Logger};
-use slog_async::Async;
+use regex::Regex;
+use slog::{self, Drain, Logger};
 use slog_kvfilter::KVFilter;
 use slog_term::{self, CompactFormat, FullFormat, PlainDecorator, TermDecorator};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::io;
+use std::str::FromStr;
+use std::sync::Mutex;
+use trackable::error::ErrorKindExt;
 
-use misc::{module_and_line, timezone_to_timestamp_fn};
-use types::KVFilterParameters;
-use types::{Format, Severity, SourceLocation, TimeZone};
-use {Build, Config, Result};
+use misc::{
+    json_drain, source_location_logger, with_fields, with_metadata, AsyncDrain, AsyncOrSyncDrain,
+    LogfmtDrain, Timestamper,
+};
+use rate_limit::RateLimitDrain;
+use redact::{RedactDrain, Redactor};
+use template::{Template, TemplateDrain};
+use types::{DroppedRecordCounter, OverflowStrategy};
+use types::{
+    FieldValue, Format, MetadataFields, Severity, SourceLocation, TimeZone, TimestampFormat,
+};
+use types::{KVFilterParameters, LevelDirectives, LevelHandle, RateLimit};
+use {Build, Config, Error, ErrorKind, LoggerGuard, Result};
 
 /// A logger builder which build loggers that output log records to the terminal.
 ///
@@ -18,10 +31,20 @@ use {Build, Config, Result};
 pub struct TerminalLoggerBuilder {
     format: Format,
     source_location: SourceLocation,
+    source_location_key: String,
+    metadata: MetadataFields,
+    fields: Vec<(String, FieldValue)>,
+    redactor: Redactor,
+    rate_limit: Option<RateLimit>,
     timezone: TimeZone,
+    timestamp_format: TimestampFormat,
     destination: Destination,
     level: Severity,
+    level_directives: LevelDirectives,
     channel_size: usize,
+    sync: bool,
+    overflow_strategy: OverflowStrategy,
+    dropped_records: DroppedRecordCounter,
     kvfilterparameters: Option<KVFilterParameters>,
 }
 impl TerminalLoggerBuilder {
@@ -30,10 +53,20 @@ impl TerminalLoggerBuilder {
         TerminalLoggerBuilder {
             format: Format::default(),
             source_location: SourceLocation::default(),
+            source_location_key: default_source_location_key(),
+            metadata: MetadataFields::default(),
+            fields: Vec::new(),
+            redactor: Redactor::default(),
+            rate_limit: None,
             timezone: TimeZone::default(),
+            timestamp_format: TimestampFormat::default(),
             destination: Destination::default(),
             level: Severity::default(),
+            level_directives: LevelDirectives::default(),
             channel_size: 1024,
+            sync: false,
+            overflow_strategy: OverflowStrategy::default(),
+            dropped_records: DroppedRecordCounter::new(),
             kvfilterparameters: None,
         }
     }
@@ -50,12 +83,63 @@ impl TerminalLoggerBuilder {
         self
     }
 
+    /// Sets the key under which the source code location is added to log records.
+    ///
+    /// The default value is `"module"`.
+    pub fn source_location_key(&mut self, key: &str) -> &mut Self {
+        self.source_location_key = key.to_owned();
+        self
+    }
+
+    /// Sets the metadata fields (e.g., process ID and thread name) added to every log record.
+    pub fn metadata(&mut self, fields: MetadataFields) -> &mut Self {
+        self.metadata = fields;
+        self
+    }
+
+    /// Adds a static key/value pair to every log record.
+    pub fn field<V: Into<FieldValue>>(&mut self, key: &str, value: V) -> &mut Self {
+        self.fields.push((key.to_owned(), value.into()));
+        self
+    }
+
+    /// Adds a key whose values are replaced with `"[REDACTED]"` in log records.
+    ///
+    /// Keys are compared case-insensitively.
+    pub fn redact_key(&mut self, key: &str) -> &mut Self {
+        self.redactor.add_key(key);
+        self
+    }
+
+    /// Adds a pattern whose matches in the messages and the string values of log records
+    /// are replaced with `"[REDACTED]"`.
+    pub fn redact_pattern(&mut self, pattern: Regex) -> &mut Self {
+        self.redactor.add_pattern(pattern);
+        self
+    }
+
+    /// Sets the rate limit of log records.
+    ///
+    /// See the documentation of [`RateLimit`] for more details.
+    ///
+    /// [`RateLimit`]: ../types/struct.RateLimit.html
+    pub fn rate_limit(&mut self, limit: RateLimit) -> &mut Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
     /// Sets the time zone which this logger will use.
     pub fn timezone(&mut self, timezone: TimeZone) -> &mut Self {
         self.timezone = timezone;
         self
     }
 
+    /// Sets the format of timestamps.
+    pub fn timestamp_format(&mut self, format: TimestampFormat) -> &mut Self {
+        self.timestamp_format = format;
+        self
+    }
+
     /// Sets the destination to which log records will be outputted.
     pub fn destination(&mut self, destination: Destination) -> &mut Self {
         self.destination = destination;
@@ -68,12 +152,58 @@ impl TerminalLoggerBuilder {
         self
     }
 
+    /// Sets per-module log level directives.
+    ///
+    /// The directives take precedence over the log level set by [`level`]
+    /// for the matching modules.
+    ///
+    /// [`level`]: #method.level
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sloggers::terminal::TerminalLoggerBuilder;
+    ///
+    /// let mut builder = TerminalLoggerBuilder::new();
+    /// builder.level_directives("info,my_crate::db=debug,hyper=warn".parse().unwrap());
+    /// ```
+    pub fn level_directives(&mut self, directives: LevelDirectives) -> &mut Self {
+        self.level_directives = directives;
+        self
+    }
+
     /// Sets the size of the asynchronous channel of this logger.
     pub fn channel_size(&mut self, channel_size: usize) -> &mut Self {
         self.channel_size = channel_size;
         self
     }
 
+    /// Sets whether this logger processes log records synchronously or not.
+    ///
+    /// If `true` is specified, log records are outputted by the calling thread
+    /// (the formatted drain is protected by a mutex) instead of a background thread.
+    /// It is slower but no log records are lost when the process exits.
+    /// The settings of the asynchronous channel (e.g., `channel_size`) are ignored in that case.
+    ///
+    /// The default value is `false`.
+    pub fn sync(&mut self, sync: bool) -> &mut Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Sets the behavior of this logger when its asynchronous channel is full.
+    pub fn overflow_strategy(&mut self, overflow_strategy: OverflowStrategy) -> &mut Self {
+        self.overflow_strategy = overflow_strategy;
+        self
+    }
+
+    /// Returns the counter of the log records dropped due to overflows of the asynchronous channel.
+    ///
+    /// The counter is shared by all the loggers built by this builder.
+    pub fn dropped_record_counter(&self) -> DroppedRecordCounter {
+        self.dropped_records.clone()
+    }
+
     /// Sets [`KVFilter`].
     ///
     /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
@@ -82,16 +212,118 @@ impl TerminalLoggerBuilder {
         self
     }
 
-    fn build_with_drain<D>(&self, drain: D) -> Logger
+    /// Builds a logger and a handle for changing the log level of the logger at runtime.
+    pub fn build_with_level_handle(&self) -> Result<(Logger, LevelHandle)> {
+        track!(self.build_logger(None))
+    }
+
+    pub(crate) fn build_logger(
+        &self,
+        guard: Option<&mut LoggerGuard>,
+    ) -> Result<(Logger, LevelHandle)> {
+        if let Some(limit) = self.rate_limit {
+            track!(limit.validate())?;
+        }
+        let severity = self.level_directives.default_severity();
+        let level = LevelHandle::new(severity.unwrap_or(self.level))
+            .with_directives(&self.level_directives);
+        let timestamper = track!(Timestamper::new(
+            self.timestamp_format.clone(),
+            self.timezone
+        ))?;
+        let mut metadata = self.metadata.clone();
+        let logger = match self.format {
+            Format::Full => {
+                let decorator = self.destination.to_decorator();
+                let format = FullFormat::new(decorator)
+                    .use_custom_timestamp(move |w: &mut dyn io::Write| timestamper.write(w));
+                self.build_with_drain(format.build(), &level, guard)
+            }
+            Format::Compact => {
+                let decorator = self.destination.to_decorator();
+                let format = CompactFormat::new(decorator)
+                    .use_custom_timestamp(move |w: &mut dyn io::Write| timestamper.write(w));
+                self.build_with_drain(format.build(), &level, guard)
+            }
+            Format::Json => match self.destination {
+                Destination::Stdout => {
+                    self.build_with_drain(json_drain(io::stdout(), timestamper), &level, guard)
+                }
+                Destination::Stderr => {
+                    self.build_with_drain(json_drain(io::stderr(), timestamper), &level, guard)
+                }
+            },
+            Format::Logfmt => match self.destination {
+                Destination::Stdout => {
+                    let drain = LogfmtDrain::new(io::stdout(), timestamper);
+                    self.build_with_drain(drain, &level, guard)
+                }
+                Destination::Stderr => {
+                    let drain = LogfmtDrain::new(io::stderr(), timestamper);
+                    self.build_with_drain(drain, &level, guard)
+                }
+            },
+            Format::Template(ref template) => {
+                let template = track!(template.parse::<Template>())?;
+                metadata.thread_name |= template.uses_thread();
+                match self.destination {
+                    Destination::Stdout => {
+                        let drain = TemplateDrain::new(io::stdout(), template, timestamper);
+                        self.build_with_drain(drain, &level, guard)
+                    }
+                    Destination::Stderr => {
+                        let drain = TemplateDrain::new(io::stderr(), template, timestamper);
+                        self.build_with_drain(drain, &level, guard)
+                    }
+                }
+            }
+        };
+        let logger = with_fields(logger, &self.fields);
+        Ok((with_metadata(logger, &metadata), level))
+    }
+
+    fn build_with_drain<D>(
+        &self,
+        drain: D,
+        level: &LevelHandle,
+        mut guard: Option<&mut LoggerGuard>,
+    ) -> Logger
     where
         D: Drain + Send + 'static,
         D::Err: Debug,
     {
-        // async inside, level and key value filters outside for speed
-        let drain = Async::new(drain.fuse())
-            .chan_size(self.channel_size)
-            .build()
-            .fuse();
+        let drain = RedactDrain::new(drain, self.redactor.clone());
+
+        // The source code location is controlled by the template
+        let source_location = match self.format {
+            Format::Template(_) => SourceLocation::None,
+            _ => self.source_location,
+        };
+
+        // The worker thread must be joined after the pending summaries of the rate limit are outputted
+        let mut async_guard = guard.as_ref().map(|_| LoggerGuard::new());
+
+        // async (or mutex) inside, level and key value filters outside for speed
+        let drain = if self.sync {
+            AsyncOrSyncDrain::Sync(Mutex::new(drain.fuse()).fuse())
+        } else {
+            AsyncOrSyncDrain::Async(
+                AsyncDrain::new(
+                    drain.fuse(),
+                    self.channel_size,
+                    self.overflow_strategy,
+                    self.dropped_records.clone(),
+                    async_guard.as_mut(),
+                )
+                .fuse(),
+            )
+        };
+
+        // The rate limit is applied after the filters, so that filtered out records do not use it up
+        let drain = RateLimitDrain::new(drain, self.rate_limit, guard.as_deref_mut());
+        if let (Some(guard), Some(async_guard)) = (guard, async_guard) {
+            guard.merge(async_guard);
+        }
 
         if let Some(ref p) = self.kvfilterparameters {
             let kvdrain = KVFilter::new(drain, p.severity.as_level())
@@ -100,23 +332,17 @@ impl TerminalLoggerBuilder {
                 .always_suppress_on_regex(p.always_suppress_on_regex.clone())
                 .only_pass_on_regex(p.only_pass_on_regex.clone());
 
-            let drain = self.level.set_level_filter(kvdrain.fuse());
+            let drain = level
+                .set_level_filter(kvdrain.fuse())
+                .directives(self.level_directives.clone());
 
-            match self.source_location {
-                SourceLocation::None => Logger::root(drain.fuse(), o!()),
-                SourceLocation::ModuleAndLine => {
-                    Logger::root(drain.fuse(), o!("module" => FnValue(module_and_line)))
-                }
-            }
+            source_location_logger(drain.fuse(), source_location, &self.source_location_key)
         } else {
-            let drain = self.level.set_level_filter(drain.fuse());
+            let drain = level
+                .set_level_filter(drain.fuse())
+                .directives(self.level_directives.clone());
 
-            match self.source_location {
-                SourceLocation::None => Logger::root(drain.fuse(), o!()),
-                SourceLocation::ModuleAndLine => {
-                    Logger::root(drain.fuse(), o!("module" => FnValue(module_and_line)))
-                }
-            }
+            source_location_logger(drain.fuse(), source_location, &self.source_location_key)
         }
     }
 }
@@ -127,20 +353,15 @@ impl Default for TerminalLoggerBuilder {
 }
 impl Build for TerminalLoggerBuilder {
     fn build(&self) -> Result<Logger> {
-        let decorator = self.destination.to_decorator();
-        let timestamp = timezone_to_timestamp_fn(self.timezone);
-        let logger = match self.format {
-            Format::Full => {
-                let format = FullFormat::new(decorator).use_custom_timestamp(timestamp);
-                self.build_with_drain(format.build())
-            }
-            Format::Compact => {
-                let format = CompactFormat::new(decorator).use_custom_timestamp(timestamp);
-                self.build_with_drain(format.build())
-            }
-        };
+        let (logger, _) = track!(self.build_with_level_handle())?;
         Ok(logger)
     }
+
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let mut guard = LoggerGuard::new();
+        let (logger, _) = track!(self.build_logger(Some(&mut guard)))?;
+        Ok((logger, guard))
+    }
 }
 
 /// The destination to which log records will be outputted.
@@ -154,18 +375,24 @@ impl Build for TerminalLoggerBuilder {
 ///
 /// assert_eq!(Destination::default(), Destination::Stdout);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Destination {
     /// Standard output.
+    #[default]
     Stdout,
 
     /// Standard error.
     Stderr,
 }
-impl Default for Destination {
-    fn default() -> Self {
-        Destination::Stdout
+impl FromStr for Destination {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "stdout" => Ok(Destination::Stdout),
+            "stderr" => Ok(Destination::Stderr),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined destination: {:?}", s),
+        }
     }
 }
 impl Destination {
@@ -196,7 +423,7 @@ impl slog_term::Decorator for Decorator {
         f: F,
     ) -> io::Result<()>
     where
-        F: FnOnce(&mut slog_term::RecordDecorator) -> io::Result<()>,
+        F: FnOnce(&mut dyn slog_term::RecordDecorator) -> io::Result<()>,
     {
         match *self {
             Decorator::Term(ref d) => d.with_record(record, logger_values, f),
@@ -207,12 +434,22 @@ impl slog_term::Decorator for Decorator {
 }
 
 /// The configuration of `TerminalLoggerBuilder`.
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TerminalLoggerConfig {
     /// Log level.
     #[serde(default)]
     pub level: Severity,
 
+    /// Per-module log level directives.
+    ///
+    /// This can be a directive string (e.g., `"info,my_crate::db=debug,hyper=warn"`)
+    /// or a table of module paths and severities.
+    /// See the documentation of [`LevelDirectives`] for more details.
+    ///
+    /// [`LevelDirectives`]: ../types/struct.LevelDirectives.html
+    #[serde(default)]
+    pub levels: Option<LevelDirectives>,
+
     /// Log record format.
     #[serde(default)]
     pub format: Format,
@@ -221,10 +458,40 @@ pub struct TerminalLoggerConfig {
     #[serde(default)]
     pub source_location: SourceLocation,
 
+    /// The key under which the source code location is added to log records.
+    #[serde(default = "default_source_location_key")]
+    pub source_location_key: String,
+
+    /// Metadata fields added to every log record.
+    #[serde(default)]
+    pub metadata: MetadataFields,
+
+    /// Static key/value pairs added to every log record.
+    ///
+    /// `${NAME}` in string values is replaced with the environment variable `NAME`.
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldValue>,
+
+    /// Keys whose values are replaced with `"[REDACTED]"` (compared case-insensitively).
+    #[serde(default)]
+    pub redact_keys: Vec<String>,
+
+    /// Regular expressions whose matches in messages and string values are replaced with `"[REDACTED]"`.
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+
+    /// Rate limit of log records.
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+
     /// Time Zone.
     #[serde(default)]
     pub timezone: TimeZone,
 
+    /// Timestamp format.
+    #[serde(default)]
+    pub timestamp_format: TimestampFormat,
+
     /// Output destination.
     #[serde(default)]
     pub destination: Destination,
@@ -232,21 +499,86 @@ pub struct TerminalLoggerConfig {
     /// Asynchronous channel size
     #[serde(default = "default_channel_size")]
     pub channel_size: usize,
+
+    /// Whether to process log records synchronously.
+    #[serde(default)]
+    pub sync: bool,
+
+    /// Behavior when the asynchronous channel is full.
+    #[serde(default)]
+    pub overflow_strategy: OverflowStrategy,
+
+    /// [`KVFilter`] parameters.
+    ///
+    /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
+    #[serde(default)]
+    pub kvfilter: Option<KVFilterParameters>,
+}
+impl Default for TerminalLoggerConfig {
+    fn default() -> Self {
+        TerminalLoggerConfig {
+            level: Severity::default(),
+            levels: None,
+            format: Format::default(),
+            source_location: SourceLocation::default(),
+            source_location_key: default_source_location_key(),
+            metadata: MetadataFields::default(),
+            fields: BTreeMap::new(),
+            redact_keys: Vec::new(),
+            redact_patterns: Vec::new(),
+            rate_limit: None,
+            timezone: TimeZone::default(),
+            timestamp_format: TimestampFormat::default(),
+            destination: Destination::default(),
+            channel_size: default_channel_size(),
+            sync: false,
+            overflow_strategy: OverflowStrategy::default(),
+            kvfilter: None,
+        }
+    }
 }
 impl Config for TerminalLoggerConfig {
     type Builder = TerminalLoggerBuilder;
     fn try_to_builder(&self) -> Result<Self::Builder> {
         let mut builder = TerminalLoggerBuilder::new();
         builder.level(self.level);
-        builder.format(self.format);
+        if let Some(ref d) = self.levels {
+            builder.level_directives(d.clone());
+        }
+        builder.format(self.format.clone());
         builder.source_location(self.source_location);
+        builder.source_location_key(&self.source_location_key);
+        builder.metadata(self.metadata.clone());
+        for (key, value) in &self.fields {
+            builder.field(key, track!(value.expand_env(), "key={:?}", key)?);
+        }
+        for key in &self.redact_keys {
+            builder.redact_key(key);
+        }
+        for pattern in &self.redact_patterns {
+            let pattern = track!(Regex::new(pattern).map_err(|e| ErrorKind::Invalid.cause(e)))?;
+            builder.redact_pattern(pattern);
+        }
+        if let Some(limit) = self.rate_limit {
+            builder.rate_limit(limit);
+        }
         builder.timezone(self.timezone);
+        builder.timestamp_format(self.timestamp_format.clone());
         builder.destination(self.destination);
         builder.channel_size(self.channel_size);
+        builder.sync(self.sync);
+        builder.overflow_strategy(self.overflow_strategy);
+        if let Some(ref p) = self.kvfilter {
+            builder.kvfilter(p.clone());
+        }
         Ok(builder)
     }
 }
 
+fn default_source_location_key() -> String {
+    "module".to_owned()
+}
+
 fn default_channel_size() -> usize {
     1024
 }
@@ -0,0 +1,300 @@
+//! Bridge from `tracing` to loggers built by this crate.
+//!
+//! This module is available only if the `tracing` feature is enabled.
+use slog::{self, Drain, Logger, Record, RecordLocation, RecordStatic, Serializer, KV};
+use std::fmt;
+use tracing_core::field::{Field, Visit};
+use tracing_core::span::{Attributes, Id, Record as SpanRecord};
+use tracing_core::subscriber::Interest;
+use tracing_core::{dispatcher, Dispatch, Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::{LookupSpan, Registry};
+use trackable::error::ErrorKindExt;
+
+use types::Severity;
+use {ErrorKind, Result};
+
+/// Sets the logger for the events emitted via `tracing` crate.
+///
+/// A subscriber consisting of `SlogLayer` is installed as the global default subscriber of `tracing`,
+/// and kept for the rest of the process.
+///
+/// If the `tracing` feature is enabled, [`init`] calls this function too.
+///
+/// # Errors
+///
+/// Returns an `ErrorKind::Other` error if a global default subscriber has already been set
+/// (e.g., by the application or by an earlier call).
+/// The error can be ignored, in which case `tracing` events keep going to the existing subscriber.
+///
+/// [`init`]: ../fn.init.html
+pub fn set_global_default(logger: Logger) -> Result<()> {
+    let subscriber = Registry::default().with(SlogLayer::new(logger));
+    track!(dispatcher::set_global_default(Dispatch::new(subscriber))
+        .map_err(|e| ErrorKind::Other.cause(e)))?;
+    Ok(())
+}
+
+/// A `tracing_subscriber::Layer` which forwards `tracing` events to a slog `Logger`.
+///
+/// Each event is outputted as a log record:
+///
+/// - The level of the event is converted to `Severity` (see the `From<Level>` implementation of `Severity`)
+/// - The `message` field of the event becomes the message of the record
+/// - The other fields of the event become the key/value pairs of the record
+/// - The fields of the spans enclosing the event are also added to the record,
+///   as well as the `span` key which holds the names of the spans (e.g., `"outer:inner"`)
+///
+/// Events are filtered by the level of the logger,
+/// so changes made via a `LevelHandle` are applied to them as well.
+///
+/// # Examples
+///
+/// ```
+/// extern crate sloggers;
+/// extern crate tracing;
+/// extern crate tracing_subscriber;
+///
+/// # fn main() {
+/// use sloggers::Build;
+/// use sloggers::terminal::TerminalLoggerBuilder;
+/// use sloggers::tracing_bridge::SlogLayer;
+/// use tracing_subscriber::layer::SubscriberExt;
+/// use tracing_subscriber::Registry;
+///
+/// let logger = TerminalLoggerBuilder::new().build().unwrap();
+/// let subscriber = Registry::default().with(SlogLayer::new(logger));
+///
+/// tracing::subscriber::with_default(subscriber, || {
+///     let span = tracing::info_span!("request", id = 10);
+///     let _enter = span.enter();
+///     tracing::info!(user = "foo", "Hello World!");
+/// });
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SlogLayer {
+    logger: Logger,
+}
+impl SlogLayer {
+    /// Makes a new `SlogLayer` instance which outputs events via `logger`.
+    pub fn new(logger: Logger) -> Self {
+        SlogLayer { logger }
+    }
+
+    /// Returns the logger of this layer.
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+}
+impl<S> Layer<S> for SlogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // The level of the logger may be changed at runtime
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata, _ctx: Context<S>) -> bool {
+        let severity = Severity::from(*metadata.level());
+        Drain::is_enabled(&self.logger, severity.as_level())
+    }
+
+    fn on_new_span(&self, attrs: &Attributes, id: &Id, ctx: Context<S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &SpanRecord, ctx: Context<S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut extensions = span.extensions_mut();
+            if let Some(fields) = extensions.get_mut::<Fields>() {
+                values.record(fields);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event, ctx: Context<S>) {
+        let metadata = event.metadata();
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let mut span_names = Vec::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope {
+                if let Some(span_fields) = span.extensions().get::<Fields>() {
+                    fields.values.extend(span_fields.values.iter().cloned());
+                }
+                span_names.push(span.name());
+            }
+        }
+        if !span_names.is_empty() {
+            span_names.reverse();
+            fields
+                .values
+                .push(("span", FieldValue::Str(span_names.join(":"))));
+        }
+
+        let location = RecordLocation {
+            file: metadata.file().unwrap_or("<unknown>"),
+            line: metadata.line().unwrap_or(0),
+            column: 0,
+            function: "",
+            module: metadata.module_path().unwrap_or("<unknown>"),
+        };
+        let record_static = RecordStatic {
+            location: &location,
+            level: Severity::from(*metadata.level()).as_level(),
+            tag: metadata.target(),
+        };
+        let message = fields.message.take().unwrap_or_default();
+        self.logger.log(&Record::new(
+            &record_static,
+            &format_args!("{}", message),
+            slog::BorrowedKV(&fields),
+        ));
+    }
+}
+
+impl From<Level> for Severity {
+    fn from(level: Level) -> Self {
+        if level == Level::TRACE {
+            Severity::Trace
+        } else if level == Level::DEBUG {
+            Severity::Debug
+        } else if level == Level::INFO {
+            Severity::Info
+        } else if level == Level::WARN {
+            Severity::Warning
+        } else {
+            Severity::Error
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum FieldValue {
+    Str(String),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+}
+
+#[derive(Debug, Default)]
+struct Fields {
+    message: Option<String>,
+    values: Vec<(&'static str, FieldValue)>,
+}
+impl Visit for Fields {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.values.push((field.name(), FieldValue::I64(value)));
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.values.push((field.name(), FieldValue::U64(value)));
+    }
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.values.push((field.name(), FieldValue::F64(value)));
+    }
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.values.push((field.name(), FieldValue::Bool(value)));
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_owned());
+        } else {
+            self.values
+                .push((field.name(), FieldValue::Str(value.to_owned())));
+        }
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+        } else {
+            self.values
+                .push((field.name(), FieldValue::Str(format!("{:?}", value))));
+        }
+    }
+}
+impl KV for Fields {
+    fn serialize(&self, _record: &Record, serializer: &mut dyn Serializer) -> slog::Result {
+        for &(key, ref value) in &self.values {
+            match *value {
+                FieldValue::Str(ref v) => serializer.emit_str(key, v)?,
+                FieldValue::I64(v) => serializer.emit_i64(key, v)?,
+                FieldValue::U64(v) => serializer.emit_u64(key, v)?,
+                FieldValue::F64(v) => serializer.emit_f64(key, v)?,
+                FieldValue::Bool(v) => serializer.emit_bool(key, v)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log;
+    use std::fs;
+    use std::thread;
+    use std::time::Duration;
+    use tempdir::TempDir;
+    use tracing;
+
+    use super::*;
+    use file::FileLoggerBuilder;
+    use test_util::lock_global_state;
+    use types::Format;
+    use {init, Build, LoggerConfig, Result};
+
+    #[test]
+    fn slog_layer_works() -> Result<()> {
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .format(Format::Json)
+            .build()?;
+        let subscriber = Registry::default().with(SlogLayer::new(logger));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("outer", id = 10);
+            let _enter = span.enter();
+            let span = tracing::info_span!("inner", name = "bar");
+            let _enter = span.enter();
+            tracing::info!(count = 3, "foo");
+            tracing::debug!("baz");
+        });
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(dir.path().join("foo.log"))?;
+        assert!(content.contains(r#""msg":"foo""#));
+        assert!(content.contains(r#""count":3"#));
+        assert!(content.contains(r#""id":10"#));
+        assert!(content.contains(r#""name":"bar""#));
+        assert!(content.contains(r#""span":"outer:inner""#));
+        assert!(!content.contains("baz"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn init_keeps_existing_global_subscriber() -> Result<()> {
+        let _lock = lock_global_state();
+        let dir = TempDir::new("sloggers_test")?;
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log")).build()?;
+        let _ = set_global_default(logger.clone());
+
+        let error = set_global_default(logger).err();
+        assert_eq!(error.map(|e| e.kind().clone()), Some(ErrorKind::Other));
+
+        let guard = init(&LoggerConfig::default())?;
+        assert_eq!(log::max_level(), log::LevelFilter::Info);
+
+        guard.level_handle().set(Severity::Debug);
+        assert_eq!(log::max_level(), log::LevelFilter::Debug);
+
+        Ok(())
+    }
+}
@@ -1,8 +1,17 @@
 //! Commonly used types.
+use chrono::FixedOffset;
+use chrono_tz::Tz;
+use log;
 use regex::Regex;
-use slog::{Drain, Level, LevelFilter};
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use slog::{Drain, Level, LevelFilter, OwnedKVList, Record};
 use slog_kvfilter::KVFilterList;
+use std::env;
+use std::fmt;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 use {Error, ErrorKind};
 
@@ -26,11 +35,14 @@ use {Error, ErrorKind};
 ///
 /// See [slog's documentation](https://docs.rs/slog/2.2.3/slog/#notable-details) for more details.
 #[allow(missing_docs)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
     Trace,
     Debug,
+    #[default]
     Info,
     Warning,
     Error,
@@ -53,10 +65,18 @@ impl Severity {
     pub fn set_level_filter<D: Drain>(self, drain: D) -> LevelFilter<D> {
         LevelFilter::new(drain, self.as_level())
     }
-}
-impl Default for Severity {
-    fn default() -> Self {
-        Severity::Info
+
+    /// Converts `Severity` to `log::LevelFilter`.
+    ///
+    /// Because `log` crate has no critical level, `Severity::Critical` is converted to `Error`.
+    pub fn as_log_level_filter(self) -> log::LevelFilter {
+        match self {
+            Severity::Trace => log::LevelFilter::Trace,
+            Severity::Debug => log::LevelFilter::Debug,
+            Severity::Info => log::LevelFilter::Info,
+            Severity::Warning => log::LevelFilter::Warn,
+            Severity::Error | Severity::Critical => log::LevelFilter::Error,
+        }
     }
 }
 impl FromStr for Severity {
@@ -74,6 +94,431 @@ impl FromStr for Severity {
     }
 }
 
+/// A handle for changing the log level of an already built logger.
+///
+/// Handles can be obtained via the `build_with_level_handle` method of logger builders
+/// (e.g., [`TerminalLoggerBuilder::build_with_level_handle`]).
+/// Cloned handles share the same log level.
+///
+/// [`TerminalLoggerBuilder::build_with_level_handle`]: ../terminal/struct.TerminalLoggerBuilder.html#method.build_with_level_handle
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate slog;
+/// extern crate sloggers;
+///
+/// use sloggers::terminal::TerminalLoggerBuilder;
+/// use sloggers::types::Severity;
+///
+/// # fn main() {
+/// let (logger, handle) = TerminalLoggerBuilder::new().build_with_level_handle().unwrap();
+/// assert_eq!(handle.get(), Severity::Info);
+/// debug!(logger, "This record is discarded");
+///
+/// handle.set(Severity::Debug);
+/// debug!(logger, "This record is outputted");
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LevelHandle {
+    severity: Arc<AtomicUsize>,
+    children: Vec<LevelHandle>,
+    module_severity: Option<Severity>,
+    stdlog: Arc<Mutex<StdlogSync>>,
+}
+impl LevelHandle {
+    /// Makes a new `LevelHandle` instance which has the given severity.
+    pub fn new(severity: Severity) -> Self {
+        LevelHandle {
+            severity: Arc::new(AtomicUsize::new(severity as usize)),
+            children: Vec::new(),
+            module_severity: None,
+            stdlog: Arc::default(),
+        }
+    }
+
+    /// Makes a `LevelHandle` which changes the levels of all the given handles at once.
+    ///
+    /// The initial severity of the resulting handle is the most verbose one among `handles`.
+    pub fn merge(handles: Vec<LevelHandle>) -> Self {
+        let severity = handles
+            .iter()
+            .map(LevelHandle::get)
+            .min()
+            .unwrap_or_default();
+        let module_severity = handles.iter().filter_map(|h| h.module_severity).min();
+        LevelHandle {
+            severity: Arc::new(AtomicUsize::new(severity as usize)),
+            children: handles,
+            module_severity,
+            stdlog: Arc::default(),
+        }
+    }
+
+    /// Returns the current severity of this handle.
+    pub fn get(&self) -> Severity {
+        match self.severity.load(Ordering::Relaxed) {
+            0 => Severity::Trace,
+            1 => Severity::Debug,
+            2 => Severity::Info,
+            3 => Severity::Warning,
+            4 => Severity::Error,
+            _ => Severity::Critical,
+        }
+    }
+
+    /// Changes the severity of this handle.
+    ///
+    /// Loggers associated with this handle will apply the new severity to subsequent log records.
+    pub fn set(&self, severity: Severity) {
+        self.severity.store(severity as usize, Ordering::Relaxed);
+        for child in &self.children {
+            child.set(severity);
+        }
+        self.update_stdlog_max_level();
+    }
+
+    /// Makes the max level of `log` crate follow the severity of this handle.
+    ///
+    /// After calling this method, `log::max_level()` is updated whenever
+    /// the severity of this handle (or its clones) is changed,
+    /// so that the log records emitted via `log` crate which would be discarded by
+    /// the logger are filtered out before being passed to it.
+    ///
+    /// This is intended to be used together with [`set_stdlog_logger`]
+    /// (note that [`init`] calls this method automatically).
+    ///
+    /// [`set_stdlog_logger`]: ../fn.set_stdlog_logger.html
+    /// [`init`]: ../fn.init.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate log;
+    /// extern crate sloggers;
+    ///
+    /// use sloggers::terminal::TerminalLoggerBuilder;
+    /// use sloggers::types::Severity;
+    ///
+    /// # fn main() {
+    /// let (logger, handle) = TerminalLoggerBuilder::new().build_with_level_handle().unwrap();
+    /// sloggers::set_stdlog_logger(logger).unwrap();
+    /// assert_eq!(log::max_level(), log::LevelFilter::Info);
+    ///
+    /// handle.sync_stdlog_max_level();
+    ///
+    /// handle.set(Severity::Debug);
+    /// assert_eq!(log::max_level(), log::LevelFilter::Debug);
+    ///
+    /// // Only warnings and errors are accepted from `log` crate
+    /// handle.set_stdlog_severity(Some(Severity::Warning));
+    /// assert_eq!(log::max_level(), log::LevelFilter::Warn);
+    /// # }
+    /// ```
+    pub fn sync_stdlog_max_level(&self) {
+        self.stdlog.lock().unwrap_or_else(|e| e.into_inner()).synced = true;
+        self.update_stdlog_max_level();
+    }
+
+    /// Returns the severity applied only to the log records emitted via `log` crate.
+    pub fn stdlog_severity(&self) -> Option<Severity> {
+        self.stdlog
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .severity
+    }
+
+    /// Sets the severity applied only to the log records emitted via `log` crate.
+    ///
+    /// It is used to suppress noisy log records from dependencies
+    /// without changing the log level of the records emitted via `slog`.
+    /// Because the records are also filtered by the logger,
+    /// a severity more verbose than the one of this handle has no effect.
+    ///
+    /// If `None` (the default), the records are only filtered by the logger.
+    ///
+    /// This takes effect only after [`sync_stdlog_max_level`] is called.
+    ///
+    /// [`sync_stdlog_max_level`]: #method.sync_stdlog_max_level
+    pub fn set_stdlog_severity(&self, severity: Option<Severity>) {
+        self.stdlog
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .severity = severity;
+        self.update_stdlog_max_level();
+    }
+
+    pub(crate) fn with_directives(mut self, directives: &LevelDirectives) -> Self {
+        self.module_severity = directives.modules.iter().map(|&(_, s)| s).min();
+        self
+    }
+
+    fn update_stdlog_max_level(&self) {
+        let stdlog = self.stdlog.lock().unwrap_or_else(|e| e.into_inner());
+        if !stdlog.synced {
+            return;
+        }
+
+        // Modules with more verbose directives must still receive their records
+        let logger_severity = self
+            .module_severity
+            .map_or(self.get(), |s| ::std::cmp::min(s, self.get()));
+        let severity = stdlog
+            .severity
+            .map_or(logger_severity, |s| ::std::cmp::max(s, logger_severity));
+        log::set_max_level(severity.as_log_level_filter());
+    }
+
+    /// Sets `LevelHandleFilter` to `drain`.
+    pub fn set_level_filter<D: Drain>(&self, drain: D) -> LevelHandleFilter<D> {
+        LevelHandleFilter {
+            drain,
+            handle: self.clone(),
+            directives: LevelDirectives::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct StdlogSync {
+    synced: bool,
+    severity: Option<Severity>,
+}
+
+/// A drain which filters log records by the severity of a `LevelHandle`.
+///
+/// If per-module level directives are given,
+/// they take precedence over the severity of the handle for the matching modules.
+#[derive(Debug)]
+pub struct LevelHandleFilter<D> {
+    drain: D,
+    handle: LevelHandle,
+    directives: LevelDirectives,
+}
+impl<D> LevelHandleFilter<D> {
+    /// Sets per-module level directives.
+    pub fn directives(mut self, directives: LevelDirectives) -> Self {
+        self.directives = directives;
+        self
+    }
+}
+impl<D: Drain> Drain for LevelHandleFilter<D> {
+    type Ok = Option<D::Ok>;
+    type Err = D::Err;
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        let severity = self
+            .directives
+            .module_severity(record.module())
+            .unwrap_or_else(|| self.handle.get());
+        if record.level().is_at_least(severity.as_level()) && self.drain.is_enabled(record.level())
+        {
+            self.drain.log(record, values).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+    fn is_enabled(&self, level: Level) -> bool {
+        let severity = self
+            .directives
+            .modules
+            .iter()
+            .map(|&(_, severity)| severity)
+            .fold(self.handle.get(), ::std::cmp::min);
+        level.is_at_least(severity.as_level()) && self.drain.is_enabled(level)
+    }
+}
+
+/// `RUST_LOG`-style per-module log level directives.
+///
+/// A directive string is a comma separated list of `MODULE_PATH=SEVERITY` and `SEVERITY` entries
+/// (e.g., `"info,my_crate::db=debug,hyper=warn"`).
+/// Log records emitted from a module whose path starts with `MODULE_PATH` are filtered by
+/// the associated severity instead of the log level of the logger.
+/// If multiple module paths match, the longest one is used.
+/// A `SEVERITY` entry without a module path overrides the log level of the logger.
+///
+/// In addition to the names accepted by `Severity`, `"warn"` can be used for `Severity::Warning`.
+///
+/// # Examples
+///
+/// ```
+/// use sloggers::types::{LevelDirectives, Severity};
+///
+/// let directives: LevelDirectives = "info,my_crate::db=debug,hyper=warn".parse().unwrap();
+/// assert_eq!(directives.default_severity(), Some(Severity::Info));
+/// assert_eq!(directives.module_severity("my_crate::db::pool"), Some(Severity::Debug));
+/// assert_eq!(directives.module_severity("hyper"), Some(Severity::Warning));
+/// assert_eq!(directives.module_severity("my_crate::dbx"), None);
+/// assert_eq!(directives.to_string(), "info,my_crate::db=debug,hyper=warning");
+/// ```
+///
+/// From configuration text (TOML), either as a string or a table:
+///
+/// ```
+/// extern crate serdeconv;
+/// extern crate sloggers;
+///
+/// use sloggers::types::{LevelDirectives, Severity};
+///
+/// # fn main() {
+/// let directives: LevelDirectives = serdeconv::from_toml_str(r#"
+/// "my_crate::db" = "debug"
+/// hyper = "warn"
+/// "#).unwrap();
+/// assert_eq!(directives.module_severity("my_crate::db"), Some(Severity::Debug));
+/// assert_eq!(directives.module_severity("hyper::client"), Some(Severity::Warning));
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LevelDirectives {
+    default: Option<Severity>,
+    modules: Vec<(String, Severity)>,
+}
+impl LevelDirectives {
+    /// Makes a new empty `LevelDirectives` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the severity used for the modules which match no module path.
+    pub fn set_default_severity(&mut self, severity: Severity) -> &mut Self {
+        self.default = Some(severity);
+        self
+    }
+
+    /// Adds a directive for the modules whose paths start with `module`.
+    pub fn add(&mut self, module: &str, severity: Severity) -> &mut Self {
+        self.modules.retain(|(m, _)| m != module);
+        self.modules.push((module.to_owned(), severity));
+        self
+    }
+
+    /// Returns the severity used for the modules which match no module path.
+    pub fn default_severity(&self) -> Option<Severity> {
+        self.default
+    }
+
+    /// Returns the severity of the longest module path matching `module`.
+    pub fn module_severity(&self, module: &str) -> Option<Severity> {
+        self.modules
+            .iter()
+            .filter(|(prefix, _)| {
+                module.starts_with(prefix.as_str())
+                    && (module.len() == prefix.len() || module[prefix.len()..].starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|&(_, severity)| severity)
+    }
+
+    /// Returns `true` if this has no directives.
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.modules.is_empty()
+    }
+
+    fn parse_severity(s: &str) -> Result<Severity, Error> {
+        if s == "warn" {
+            Ok(Severity::Warning)
+        } else {
+            track!(s.parse())
+        }
+    }
+}
+impl FromStr for LevelDirectives {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut directives = LevelDirectives::new();
+        for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            if let Some(i) = entry.find('=') {
+                let module = entry[..i].trim();
+                track_assert!(
+                    !module.is_empty(),
+                    ErrorKind::Invalid,
+                    "Empty module path: {:?}",
+                    s
+                );
+                let severity = track!(Self::parse_severity(entry[i + 1..].trim()))?;
+                directives.add(module, severity);
+            } else {
+                let severity = track!(Self::parse_severity(entry))?;
+                directives.set_default_severity(severity);
+            }
+        }
+        Ok(directives)
+    }
+}
+impl fmt::Display for LevelDirectives {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries = Vec::new();
+        if let Some(severity) = self.default {
+            entries.push(severity_name(severity).to_owned());
+        }
+        for &(ref module, severity) in &self.modules {
+            entries.push(format!("{}={}", module, severity_name(severity)));
+        }
+        write!(f, "{}", entries.join(","))
+    }
+}
+impl Serialize for LevelDirectives {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+impl<'de> Deserialize<'de> for LevelDirectives {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(LevelDirectivesVisitor)
+    }
+}
+
+struct LevelDirectivesVisitor;
+impl<'de> Visitor<'de> for LevelDirectivesVisitor {
+    type Value = LevelDirectives;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a directive string or a table of module paths and severities"
+        )
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut directives = LevelDirectives::new();
+        while let Some((module, severity)) = map.next_entry::<String, String>()? {
+            let severity = LevelDirectives::parse_severity(&severity).map_err(de::Error::custom)?;
+            directives.add(&module, severity);
+        }
+        Ok(directives)
+    }
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Trace => "trace",
+        Severity::Debug => "debug",
+        Severity::Info => "info",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+        Severity::Critical => "critical",
+    }
+}
+
 /// Type summarizing KVFilter parameters.
 ///
 /// See the documentation of [`KVFilter`] for more details.
@@ -92,14 +537,46 @@ impl FromStr for Severity {
 /// assert!(params.only_pass_on_regex.is_none());
 /// assert!(params.always_suppress_on_regex.is_none());
 /// ```
-#[derive(Debug, Clone)]
+///
+/// From configuration text (TOML):
+///
+/// ```
+/// extern crate serdeconv;
+/// extern crate sloggers;
+///
+/// use sloggers::types::{KVFilterParameters, Severity};
+///
+/// # fn main() {
+/// let params: KVFilterParameters = serdeconv::from_toml_str(r#"
+/// severity = "debug"
+/// only_pass_on_regex = "^request_id=42$"
+///
+/// [always_suppress_any]
+/// component = ["db", "http"]
+/// "#).unwrap();
+/// assert_eq!(params.severity, Severity::Debug);
+/// assert_eq!(params.always_suppress_any.unwrap()["component"].len(), 2);
+/// assert!(params.only_pass_on_regex.unwrap().is_match("request_id=42"));
+/// # }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(missing_docs)]
 pub struct KVFilterParameters {
+    #[serde(default)]
     pub severity: Severity,
-    pub only_pass_any_on_all_keys: Option<KVFilterList>,
-    pub always_suppress_any: Option<KVFilterList>,
+
+    // NOTE: Regex fields are placed before the list fields so that this can be serialized as TOML
+    #[serde(default, with = "regex_serde")]
     pub only_pass_on_regex: Option<Regex>,
+
+    #[serde(default, with = "regex_serde")]
     pub always_suppress_on_regex: Option<Regex>,
+
+    #[serde(default)]
+    pub only_pass_any_on_all_keys: Option<KVFilterList>,
+
+    #[serde(default)]
+    pub always_suppress_any: Option<KVFilterList>,
 }
 impl Default for KVFilterParameters {
     fn default() -> Self {
@@ -113,6 +590,29 @@ impl Default for KVFilterParameters {
     }
 }
 
+mod regex_serde {
+    use super::*;
+    use serde::de::Error as DeError;
+
+    pub fn serialize<S>(regex: &Option<Regex>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        regex.as_ref().map(Regex::as_str).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Regex>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let pattern: Option<String> = Deserialize::deserialize(deserializer)?;
+        match pattern {
+            Some(p) => Regex::new(&p).map(Some).map_err(D::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
 /// The format of log records.
 ///
 /// # Examples
@@ -124,19 +624,67 @@ impl Default for KVFilterParameters {
 ///
 /// assert_eq!(Format::default(), Format::Full);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// A line template can be specified in configuration text (TOML):
+///
+/// ```
+/// extern crate serdeconv;
+/// extern crate sloggers;
+///
+/// use sloggers::terminal::TerminalLoggerConfig;
+/// use sloggers::types::Format;
+///
+/// # fn main() {
+/// let config: TerminalLoggerConfig = serdeconv::from_toml_str(r#"
+/// format = { template = "{ts} [{level}] {module} {msg} {kv}" }
+/// "#).unwrap();
+/// assert_eq!(config.format, Format::Template("{ts} [{level}] {module} {msg} {kv}".to_owned()));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Format {
     /// Full format.
+    #[default]
     Full,
 
     /// Compact format.
     Compact,
-}
-impl Default for Format {
-    fn default() -> Self {
-        Format::Full
-    }
+
+    /// JSON format.
+    ///
+    /// Each log record will be outputted as a single line JSON object.
+    /// The `level` field holds the lowercase name of the level (e.g., `"info"`), as in logfmt format.
+    Json,
+
+    /// [logfmt] format.
+    ///
+    /// Each log record will be outputted as a single line of `key=value` pairs
+    /// (e.g., `ts=2018-09-18T10:19:51.093+09:00 level=info msg="Hello World!" module=foo:12`).
+    /// Values containing spaces, `=`, quotes or control characters are quoted and escaped.
+    ///
+    /// [logfmt]: https://brandur.org/logfmt
+    Logfmt,
+
+    /// User-defined line template.
+    ///
+    /// Each log record will be outputted as a single line in which
+    /// the following placeholders are replaced:
+    ///
+    /// - `{ts}`: the timestamp
+    /// - `{level}`: the short name of the level (e.g., `INFO`, `DEBG`)
+    /// - `{level_long}`: the full name of the level (e.g., `INFO`, `DEBUG`)
+    /// - `{msg}`: the message
+    /// - `{module}`, `{file}` and `{line}`: the source code location
+    /// - `{thread}`: the name (or ID if it has no name) of the thread which emitted the record
+    /// - `{key:NAME}`: the value of the key `NAME` (or an empty string if the key does not exist)
+    /// - `{kv}`: the other key/value pairs in `key=value` form
+    ///
+    /// `{{` and `}}` are outputted as `{` and `}` respectively.
+    ///
+    /// Because the source code location is controlled by the template,
+    /// the `module` key is not added regardless of the source location setting.
+    Template(String),
 }
 impl FromStr for Format {
     type Err = Error;
@@ -144,13 +692,80 @@ impl FromStr for Format {
         match s {
             "full" => Ok(Format::Full),
             "compact" => Ok(Format::Compact),
+            "json" => Ok(Format::Json),
+            "logfmt" => Ok(Format::Logfmt),
             _ => track_panic!(ErrorKind::Invalid, "Undefined log format: {:?}", s),
         }
     }
 }
 
+/// The behavior of an asynchronous logger when its channel is full.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::types::OverflowStrategy;
+///
+/// assert_eq!(OverflowStrategy::default(), OverflowStrategy::DropAndReport);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowStrategy {
+    /// The caller is blocked until the channel has enough space.
+    Block,
+
+    /// The log record is dropped silently.
+    Drop,
+
+    /// The log record is dropped, and a warning level record reporting
+    /// the number of dropped records is emitted periodically (at most once per second).
+    #[default]
+    DropAndReport,
+}
+impl FromStr for OverflowStrategy {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "block" => Ok(OverflowStrategy::Block),
+            "drop" => Ok(OverflowStrategy::Drop),
+            "drop_and_report" => Ok(OverflowStrategy::DropAndReport),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined overflow strategy: {:?}", s),
+        }
+    }
+}
+
+/// A counter of the log records dropped due to overflows of asynchronous channels.
+///
+/// The counter of a logger builder can be obtained via its `dropped_record_counter` method
+/// (e.g., [`TerminalLoggerBuilder::dropped_record_counter`]).
+///
+/// [`TerminalLoggerBuilder::dropped_record_counter`]: ../terminal/struct.TerminalLoggerBuilder.html#method.dropped_record_counter
+#[derive(Debug, Default, Clone)]
+pub struct DroppedRecordCounter(Arc<AtomicUsize>);
+impl DroppedRecordCounter {
+    /// Makes a new `DroppedRecordCounter` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the total number of the dropped records.
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
 /// Time Zone.
 ///
+/// In addition to `Utc` and `Local`, a fixed offset from UTC (e.g., `"+09:00"`)
+/// or a named time zone of the IANA time zone database (e.g., `"Europe/Prague"`) can be specified.
+/// Named time zones take daylight saving time into account.
+///
 /// # Examples
 ///
 /// The default value:
@@ -160,17 +775,31 @@ impl FromStr for Format {
 ///
 /// assert_eq!(TimeZone::default(), TimeZone::Local);
 /// ```
-#[allow(missing_docs)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+///
+/// Parsing time zones:
+///
+/// ```
+/// use sloggers::types::TimeZone;
+///
+/// assert_eq!("utc".parse::<TimeZone>().unwrap(), TimeZone::Utc);
+/// assert_eq!("+09:00".parse::<TimeZone>().unwrap().to_string(), "+09:00");
+/// assert_eq!("Europe/Prague".parse::<TimeZone>().unwrap().to_string(), "Europe/Prague");
+/// assert!("Mars/Olympus_Mons".parse::<TimeZone>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum TimeZone {
+    /// UTC.
     Utc,
+
+    /// The local time zone of the host.
+    #[default]
     Local,
-}
-impl Default for TimeZone {
-    fn default() -> Self {
-        TimeZone::Local
-    }
+
+    /// A fixed offset from UTC.
+    Offset(FixedOffset),
+
+    /// A time zone of the IANA time zone database.
+    Named(Tz),
 }
 impl FromStr for TimeZone {
     type Err = Error;
@@ -178,13 +807,181 @@ impl FromStr for TimeZone {
         match s {
             "utc" => Ok(TimeZone::Utc),
             "local" => Ok(TimeZone::Local),
-            _ => track_panic!(ErrorKind::Invalid, "Undefined time zone: {:?}", s),
+            _ if s.starts_with('+') || s.starts_with('-') => {
+                let offset = track!(parse_utc_offset(s))?;
+                Ok(TimeZone::Offset(offset))
+            }
+            _ => match s.parse() {
+                Ok(tz) => Ok(TimeZone::Named(tz)),
+                Err(_) => track_panic!(ErrorKind::Invalid, "Undefined time zone: {:?}", s),
+            },
+        }
+    }
+}
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TimeZone::Utc => write!(f, "utc"),
+            TimeZone::Local => write!(f, "local"),
+            TimeZone::Offset(ref offset) => write!(f, "{}", offset),
+            TimeZone::Named(ref tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+impl Serialize for TimeZone {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+impl<'de> Deserialize<'de> for TimeZone {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+// Parses an offset such as "+09:00", "-0330" or "+01"
+fn parse_utc_offset(s: &str) -> Result<FixedOffset, Error> {
+    let sign = if s.starts_with('-') { -1 } else { 1 };
+    let digits = s[1..].replace(':', "");
+    track_assert!(
+        (digits.len() == 2 || digits.len() == 4) && digits.bytes().all(|b| b.is_ascii_digit()),
+        ErrorKind::Invalid,
+        "Malformed UTC offset: {:?}",
+        s
+    );
+    let number = |t: &str| t.bytes().fold(0, |n, b| n * 10 + i32::from(b - b'0'));
+    let hours = number(&digits[..2]);
+    let minutes = number(&digits[2..]);
+    track_assert!(
+        minutes < 60,
+        ErrorKind::Invalid,
+        "Malformed UTC offset: {:?}",
+        s
+    );
+    match FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)) {
+        Some(offset) => Ok(offset),
+        None => track_panic!(ErrorKind::Invalid, "UTC offset out of range: {:?}", s),
+    }
+}
+
+/// The format of timestamps in log records.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::types::TimestampFormat;
+///
+/// assert_eq!(TimestampFormat::default(), TimestampFormat::Default);
+/// ```
+///
+/// Parses from a string:
+///
+/// ```
+/// use sloggers::types::TimestampFormat;
+///
+/// assert_eq!(
+///     "rfc3339_millis".parse::<TimestampFormat>().ok(),
+///     Some(TimestampFormat::Rfc3339Millis)
+/// );
+///
+/// // Strings containing `%` are interpreted as strftime templates
+/// assert_eq!(
+///     "%Y-%m-%d %H:%M:%S".parse::<TimestampFormat>().ok(),
+///     Some(TimestampFormat::Strftime("%Y-%m-%d %H:%M:%S".to_owned()))
+/// );
+/// ```
+///
+/// From configuration text (TOML):
+///
+/// ```
+/// extern crate serdeconv;
+/// extern crate sloggers;
+///
+/// use sloggers::terminal::TerminalLoggerConfig;
+/// use sloggers::types::TimestampFormat;
+///
+/// # fn main() {
+/// let config: TerminalLoggerConfig = serdeconv::from_toml_str(r#"
+/// timestamp_format = { strftime = "%H:%M:%S" }
+/// "#).unwrap();
+/// assert_eq!(config.timestamp_format, TimestampFormat::Strftime("%H:%M:%S".to_owned()));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    /// The default format of each log format.
+    ///
+    /// `Format::Full` and `Format::Compact` use the format of `slog-term` (e.g., `Sep 18 10:19:51.093`),
+    /// and the others use RFC 3339 (e.g., `2018-09-18T10:19:51.093817+09:00`).
+    #[default]
+    Default,
+
+    /// RFC 3339 without sub-second digits (e.g., `2018-09-18T10:19:51+09:00`).
+    Rfc3339Secs,
+
+    /// RFC 3339 with milliseconds (e.g., `2018-09-18T10:19:51.093+09:00`).
+    Rfc3339Millis,
+
+    /// RFC 3339 with microseconds (e.g., `2018-09-18T10:19:51.093817+09:00`).
+    Rfc3339Micros,
+
+    /// RFC 3339 with nanoseconds (e.g., `2018-09-18T10:19:51.093817000+09:00`).
+    Rfc3339Nanos,
+
+    /// [strftime] template (e.g., `%Y-%m-%d %H:%M:%S%.3f`).
+    ///
+    /// [strftime]: https://docs.rs/chrono/0.4.6/chrono/format/strftime/index.html#specifiers
+    Strftime(String),
+
+    /// Seconds since the Unix epoch.
+    UnixSecs,
+
+    /// Milliseconds since the Unix epoch.
+    UnixMillis,
+
+    /// Nanoseconds since the Unix epoch.
+    UnixNanos,
+
+    /// No timestamp.
+    ///
+    /// Note that for `Format::Full` and `Format::Compact`,
+    /// the whitespace following the timestamp is still outputted.
+    None,
+}
+impl FromStr for TimestampFormat {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "default" => Ok(TimestampFormat::Default),
+            "rfc3339_secs" => Ok(TimestampFormat::Rfc3339Secs),
+            "rfc3339_millis" => Ok(TimestampFormat::Rfc3339Millis),
+            "rfc3339_micros" => Ok(TimestampFormat::Rfc3339Micros),
+            "rfc3339_nanos" => Ok(TimestampFormat::Rfc3339Nanos),
+            "unix_secs" => Ok(TimestampFormat::UnixSecs),
+            "unix_millis" => Ok(TimestampFormat::UnixMillis),
+            "unix_nanos" => Ok(TimestampFormat::UnixNanos),
+            "none" => Ok(TimestampFormat::None),
+            _ if s.contains('%') => Ok(TimestampFormat::Strftime(s.to_owned())),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined timestamp format: {:?}", s),
         }
     }
 }
 
 /// Source Location.
 ///
+/// The location is added to each log record under the key `"module"` by default,
+/// which can be changed via the `source_location_key` setting of each logger.
+///
 /// # Examples
 ///
 /// The default value:
@@ -194,17 +991,30 @@ impl FromStr for TimeZone {
 ///
 /// assert_eq!(SourceLocation::default(), SourceLocation::ModuleAndLine);
 /// ```
-#[allow(missing_docs)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SourceLocation {
+    /// No source code location is outputted.
     None,
+
+    /// The module path and line number (e.g., `"my_crate::foo:42"`).
+    #[default]
     ModuleAndLine,
-}
-impl Default for SourceLocation {
-    fn default() -> Self {
-        SourceLocation::ModuleAndLine
-    }
+
+    /// The file name and line number (e.g., `"foo.rs:42"`).
+    FileAndLine,
+
+    /// The file path recorded by `file!()` and line number (e.g., `"src/foo.rs:42"`).
+    FullPathAndLine,
+
+    /// The module path, followed by the file path and line number (e.g., `"my_crate::foo src/foo.rs:42"`).
+    ModuleFileAndLine,
+
+    /// The name of the function.
+    ///
+    /// If the function name is not recorded (the logging macros of `slog` currently leave it empty),
+    /// the module path is outputted instead.
+    Function,
 }
 impl FromStr for SourceLocation {
     type Err = Error;
@@ -212,6 +1022,10 @@ impl FromStr for SourceLocation {
         match s {
             "none" => Ok(SourceLocation::None),
             "module_and_line" => Ok(SourceLocation::ModuleAndLine),
+            "file_and_line" => Ok(SourceLocation::FileAndLine),
+            "full_path_and_line" => Ok(SourceLocation::FullPathAndLine),
+            "module_file_and_line" => Ok(SourceLocation::ModuleFileAndLine),
+            "function" => Ok(SourceLocation::Function),
             _ => track_panic!(
                 ErrorKind::Invalid,
                 "Undefined source code location: {:?}",
@@ -220,3 +1034,238 @@ impl FromStr for SourceLocation {
         }
     }
 }
+
+/// Metadata fields added to every log record.
+///
+/// Each enabled field is added under the following key:
+///
+/// | Field | Key | Value |
+/// |-------|-----|-------|
+/// | `pid` | `"pid"` | the ID of the current process |
+/// | `thread_id` | `"thread_id"` | the ID of the logging thread (sequentially numbered from 1 by sloggers) |
+/// | `thread_name` | `"thread"` | the name of the logging thread (`"thread-{thread_id}"` if unnamed) |
+/// | `hostname` | `"hostname"` | the host name of the running machine |
+/// | `executable` | `"executable"` | the file name of the current executable |
+/// | `app_name` | `"app_name"` | the specified application name |
+/// | `app_version` | `"app_version"` | the specified application version |
+///
+/// The thread fields are captured on the logging thread, even if the logger works asynchronously.
+/// The other fields are fixed when the logger is built.
+///
+/// # Examples
+///
+/// ```
+/// extern crate serdeconv;
+/// extern crate sloggers;
+///
+/// use sloggers::types::MetadataFields;
+///
+/// # fn main() {
+/// let fields: MetadataFields = serdeconv::from_toml_str(r#"
+/// pid = true
+/// thread_name = true
+/// app_name = "my-app"
+/// app_version = "1.2.3"
+/// "#).unwrap();
+/// assert!(fields.pid);
+/// assert!(!fields.hostname);
+/// assert_eq!(fields.app_name, Some("my-app".to_owned()));
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataFields {
+    /// Whether to add the process ID.
+    #[serde(default)]
+    pub pid: bool,
+
+    /// Whether to add the ID of the logging thread.
+    #[serde(default)]
+    pub thread_id: bool,
+
+    /// Whether to add the name of the logging thread.
+    ///
+    /// If the thread has no name, its ID is added instead.
+    #[serde(default)]
+    pub thread_name: bool,
+
+    /// Whether to add the host name.
+    #[serde(default)]
+    pub hostname: bool,
+
+    /// Whether to add the file name of the current executable.
+    #[serde(default)]
+    pub executable: bool,
+
+    /// Application name.
+    #[serde(default)]
+    pub app_name: Option<String>,
+
+    /// Application version.
+    #[serde(default)]
+    pub app_version: Option<String>,
+}
+
+/// The value of a static key/value pair added to every log record.
+///
+/// In configuration text, the value can be a string, an integer, a float or a boolean.
+/// `${NAME}` in a string value is replaced with the value of the environment variable `NAME`
+/// when the configuration is converted to a builder.
+///
+/// # Examples
+///
+/// ```
+/// extern crate serdeconv;
+/// extern crate sloggers;
+///
+/// use sloggers::types::FieldValue;
+/// use std::collections::BTreeMap;
+/// use std::env;
+///
+/// # fn main() {
+/// let fields: BTreeMap<String, FieldValue> = serdeconv::from_toml_str(r#"
+/// datacenter = "tokyo"
+/// service = "${MY_SERVICE}"
+/// shard = 3
+/// canary = false
+/// "#).unwrap();
+/// assert_eq!(fields["shard"], FieldValue::Integer(3));
+/// assert_eq!(fields["canary"], FieldValue::Bool(false));
+///
+/// env::set_var("MY_SERVICE", "api");
+/// let service = fields["service"].expand_env().unwrap();
+/// assert_eq!(service, FieldValue::String("api".to_owned()));
+/// # }
+/// ```
+#[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+impl FieldValue {
+    /// Returns a copy of this value in which `${NAME}` is replaced with the environment variable `NAME`.
+    ///
+    /// `$$` is replaced with `$`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ErrorKind::Invalid` error if a referenced variable is not defined
+    /// or a reference is not closed.
+    pub fn expand_env(&self) -> Result<Self, Error> {
+        if let FieldValue::String(ref s) = *self {
+            let expanded = track!(expand_env_vars(s))?;
+            Ok(FieldValue::String(expanded))
+        } else {
+            Ok(self.clone())
+        }
+    }
+}
+impl From<bool> for FieldValue {
+    fn from(f: bool) -> Self {
+        FieldValue::Bool(f)
+    }
+}
+impl From<i64> for FieldValue {
+    fn from(f: i64) -> Self {
+        FieldValue::Integer(f)
+    }
+}
+impl From<f64> for FieldValue {
+    fn from(f: f64) -> Self {
+        FieldValue::Float(f)
+    }
+}
+impl<'a> From<&'a str> for FieldValue {
+    fn from(f: &'a str) -> Self {
+        FieldValue::String(f.to_owned())
+    }
+}
+impl From<String> for FieldValue {
+    fn from(f: String) -> Self {
+        FieldValue::String(f)
+    }
+}
+
+fn expand_env_vars(s: &str) -> Result<String, Error> {
+    let mut expanded = String::new();
+    let mut rest = s;
+    while let Some(i) = rest.find('$') {
+        expanded.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        if rest.starts_with('$') {
+            expanded.push('$');
+            rest = &rest[1..];
+        } else if rest.starts_with('{') {
+            let end = track_assert_some!(rest.find('}'), ErrorKind::Invalid, "value={:?}", s);
+            let name = &rest[1..end];
+            match env::var(name) {
+                Ok(value) => expanded.push_str(&value),
+                Err(e) => track_panic!(
+                    ErrorKind::Invalid,
+                    "Cannot expand ${{{}}}: {} (value={:?})",
+                    name,
+                    e,
+                    s
+                ),
+            }
+            rest = &rest[end + 1..];
+        } else {
+            expanded.push('$');
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// The parameters of rate limiting.
+///
+/// Log records are rate limited per message template, level and module,
+/// using a token bucket which is refilled with `rate` tokens per second and holds at most `burst` tokens.
+/// Since the templates of messages with arguments are not available at runtime,
+/// such records are rate limited per logging statement (i.e., per source code location) instead.
+///
+/// Records which arrive when the bucket is empty are dropped,
+/// and a `"Suppressed N similar messages"` record is outputted by a background thread
+/// once the bucket is refilled, or when the logger (or its guard) is dropped.
+///
+/// Records filtered out by the log level (or `KVFilter`) do not consume tokens.
+///
+/// # Examples
+///
+/// ```
+/// extern crate serdeconv;
+/// extern crate sloggers;
+///
+/// use sloggers::types::RateLimit;
+///
+/// # fn main() {
+/// let limit: RateLimit = serdeconv::from_toml_str(r#"
+/// rate = 10.0
+/// burst = 100
+/// "#).unwrap();
+/// assert_eq!(limit, RateLimit { rate: 10.0, burst: 100 });
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// The number of records allowed per second in the long run.
+    pub rate: f64,
+
+    /// The maximum number of records allowed in a burst.
+    pub burst: u32,
+}
+impl RateLimit {
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        track_assert!(
+            self.rate > 0.0,
+            ErrorKind::Invalid,
+            "Rate must be positive: {}",
+            self.rate
+        );
+        track_assert!(self.burst > 0, ErrorKind::Invalid, "Burst must be positive");
+        Ok(())
+    }
+}